@@ -1,6 +1,16 @@
+// The demo, the headless example and the tests each use a different part of this module.
+#![allow(dead_code)]
+
 use conrod;
-use rand;
+use rand::rngs::StdRng;
+use rand::{FromEntropy, Rng, SeedableRng};
+use rusttype::FontCollection;
 use std;
+use std::time::Duration;
+use theme;
+
+/// How fast the sine wave scrolls, in radians per second.
+const SINE_SPEED: f32 = std::f32::consts::PI;
 
 widget_ids! {
     /// Unique IDs for each widget.
@@ -54,24 +64,114 @@ pub struct State {
     ball_xy: conrod::Point,
     ball_color: conrod::Color,
     sine_frequency: f32,
+    /// Advanced over time to scroll the sine wave.
+    sine_phase: f32,
     rust_logo: conrod::image::Id,
+    /// Picks where the ball moves to when "PRESS ME" is pressed.
+    rng: StdRng,
 }
 
 impl State {
     /// Simple constructor for the `DemoApp`.
     pub fn new(rust_logo: conrod::image::Id) -> Self {
+        State::with_rng(rust_logo, StdRng::from_entropy())
+    }
+
+    /// Like `new`, but the ball moves to the same sequence of positions each time for the same
+    /// `seed`.
+    pub fn with_seed(rust_logo: conrod::image::Id, seed: u64) -> Self {
+        // Repeat the little-endian bytes of `seed` to fill the 32 byte seed of `StdRng`.
+        let mut bytes = [0; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = (seed >> (i % 8 * 8)) as u8;
+        }
+
+        State::with_rng(rust_logo, StdRng::from_seed(bytes))
+    }
+
+    /// The position of the ball, relative to the middle of the XY pad.
+    pub fn ball_xy(&self) -> conrod::Point {
+        self.ball_xy
+    }
+
+    pub fn ball_color(&self) -> conrod::Color {
+        self.ball_color
+    }
+
+    fn with_rng(rust_logo: conrod::image::Id, rng: StdRng) -> Self {
         State {
             ball_xy: [0.0, 0.0],
             ball_color: conrod::color::WHITE,
             sine_frequency: 1.0,
+            sine_phase: 0.0,
             rust_logo: rust_logo,
+            rng: rng,
         }
     }
 }
 
+/// The demo GUI and its state, with the Rust logo stored as a `T`.
+pub struct Demo<T> {
+    pub ui: conrod::Ui,
+    pub ids: Ids,
+    pub state: State,
+    pub image_map: conrod::image::Map<T>,
+}
+
+impl<T> Demo<T> {
+    /// Builds the demo GUI in a window of `size`, and updates it until its layout has settled.
+    ///
+    /// With a `seed`, the ball moves to the same sequence of positions each time.
+    pub fn new(size: [f64; 2], rust_logo: T, seed: Option<u64>) -> Demo<T> {
+        let mut ui = conrod::UiBuilder::new(size).theme(theme::theme()).build();
+
+        FontCollection::from_bytes(include_bytes!("NotoSans-Regular.ttf") as &[u8])
+            .unwrap()
+            .into_fonts()
+            .for_each(|font| {
+                ui.fonts.insert(font.unwrap());
+            });
+
+        let ids = Ids::new(ui.widget_id_generator());
+
+        let mut image_map = conrod::image::Map::new();
+        let rust_logo = image_map.insert(rust_logo);
+
+        let state = match seed {
+            Some(seed) => State::with_seed(rust_logo, seed),
+            None => State::new(rust_logo),
+        };
+
+        let mut demo = Demo {
+            ui,
+            ids,
+            state,
+            image_map,
+        };
+
+        // Some widgets are positioned relative to the layout of the previous update.
+        demo.update(Duration::from_secs(0));
+        demo.update(Duration::from_secs(0));
+
+        demo
+    }
+
+    /// Updates the widgets of the GUI once, advancing animations by `delta`.
+    pub fn update(&mut self, delta: Duration) {
+        render(
+            &mut self.ui.set_widgets(),
+            &self.ids,
+            &mut self.state,
+            delta,
+        );
+    }
+}
+
 /// Renders a GUI demonstrating every widget available in Conrod.
 /// Borrowed from the standard conrod examples.
-pub fn render(ui: &mut conrod::UiCell, ids: &Ids, state: &mut State) {
+///
+/// `delta` is the time since the last call, by which animations are advanced.
+pub fn render(ui: &mut conrod::UiCell, ids: &Ids, state: &mut State, delta: Duration) {
     use conrod::{widget, Colorable, Labelable, Positionable, Sizeable, Widget};
     use std::iter::once;
 
@@ -239,8 +339,8 @@ pub fn render(ui: &mut conrod::UiCell, ids: &Ids, state: &mut State) {
         .w_h(side, side)
         .set(ids.button, ui)
     {
-        let x = rand::random::<conrod::Scalar>() * (max_x - min_x) - max_x;
-        let y = rand::random::<conrod::Scalar>() * (max_y - min_y) - max_y;
+        let x = state.rng.gen::<conrod::Scalar>() * (max_x - min_x) - max_x;
+        let y = state.rng.gen::<conrod::Scalar>() * (max_y - min_y) - max_y;
         state.ball_xy = [x, y];
     }
 
@@ -251,12 +351,13 @@ pub fn render(ui: &mut conrod::UiCell, ids: &Ids, state: &mut State) {
         state.ball_xy[1],
         min_y,
         max_y,
-    ).label("BALL XY")
-        .wh_of(ids.button)
-        .align_middle_y_of(ids.button)
-        .align_middle_x_of(ids.canvas)
-        .parent(ids.canvas)
-        .set(ids.xy_pad, ui)
+    )
+    .label("BALL XY")
+    .wh_of(ids.button)
+    .align_middle_y_of(ids.button)
+    .align_middle_x_of(ids.canvas)
+    .parent(ids.canvas)
+    .set(ids.xy_pad, ui)
     {
         state.ball_xy = [x, y];
     }
@@ -312,12 +413,17 @@ pub fn render(ui: &mut conrod::UiCell, ids: &Ids, state: &mut State) {
         state.sine_frequency = new_freq;
     }
 
-    // Use the `PlotPath` widget to display a sine wave.
+    // Use the `PlotPath` widget to display a sine wave, scrolling over time.
+    let delta_seconds = delta.as_secs() as f32 + delta.subsec_nanos() as f32 * 1e-9;
+    state.sine_phase =
+        (state.sine_phase + delta_seconds * SINE_SPEED) % (std::f32::consts::PI * 2.0);
+
+    let phase = state.sine_phase;
     let min_x = 0.0;
     let max_x = std::f32::consts::PI * 2.0 * state.sine_frequency;
     let min_y = -1.0;
     let max_y = 1.0;
-    widget::PlotPath::new(min_x, max_x, min_y, max_y, f32::sin)
+    widget::PlotPath::new(min_x, max_x, min_y, max_y, move |x: f32| (x + phase).sin())
         .kid_area_w_of(ids.canvas)
         .h(240.0)
         .down(60.0)
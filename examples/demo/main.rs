@@ -0,0 +1,977 @@
+#[macro_use]
+extern crate conrod;
+#[cfg(all(windows, not(feature = "vulkan")))]
+extern crate gfx_backend_dx12 as gfx_backend;
+#[cfg(target_os = "macos")]
+extern crate gfx_backend_metal as gfx_backend;
+#[cfg(any(all(not(windows), not(target_os = "macos")), all(windows, feature = "vulkan")))]
+extern crate gfx_backend_vulkan as gfx_backend;
+extern crate conrod_gfx_ll;
+extern crate gfx_hal;
+extern crate rand;
+extern crate rusttype;
+extern crate winit;
+
+mod gui;
+mod theme;
+
+use conrod::event::Input;
+use conrod::input::{Button, Key};
+use conrod_gfx_ll::adapter::{describe_adapter, select_adapter, ADAPTER_ENV_VAR};
+use conrod_gfx_ll::recording::{read_recording, TimedInput};
+use conrod_gfx_ll::renderer::build_render_pass;
+use conrod_gfx_ll::{AdapterPreference, AlphaMode, Error, PresentPolicy, Recorder, Renderer};
+use gfx_hal::command::OneShot;
+use gfx_hal::device::Device;
+use gfx_hal::format::{Aspects, ChannelType, Format, Swizzle};
+use gfx_hal::image::{Extent, SubresourceRange, Usage, ViewKind};
+use gfx_hal::pool::{CommandPool, CommandPoolCreateFlags};
+use gfx_hal::pso::PipelineStage;
+use gfx_hal::queue::capability::Graphics;
+use gfx_hal::queue::family::QueueFamily;
+use gfx_hal::queue::{QueueGroup, Submission};
+use gfx_hal::window::{
+    AcquireError, Backbuffer, Extent2D, FrameSync, PresentMode, Swapchain, SwapchainConfig,
+};
+use gfx_hal::Backend;
+use gfx_hal::Gpu;
+use gfx_hal::Instance;
+use gfx_hal::PhysicalDevice;
+use gfx_hal::Surface;
+use std::collections::VecDeque;
+use std::env;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::process;
+use std::sync::mpsc::{channel, RecvTimeoutError, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const WIDTH: i32 = 600;
+const HEIGHT: i32 = 420;
+
+/// The number of frames the CPU may prepare while the GPU is still rendering earlier ones.
+const FRAMES_IN_FLIGHT: usize = 2;
+
+/// The synchronization primitives and command pool used to render and present one frame.
+struct Frame<B: Backend> {
+    /// Signalled when the swapchain image is ready to be rendered to.
+    acquire_semaphore: <B as gfx_hal::Backend>::Semaphore,
+    /// Signalled when rendering is complete and the image can be presented.
+    render_semaphore: <B as gfx_hal::Backend>::Semaphore,
+    /// Signalled when the GPU has finished with the frame's command buffer.
+    fence: <B as gfx_hal::Backend>::Fence,
+    command_pool: CommandPool<B, Graphics>,
+}
+
+fn build_frame<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    graphics_queue_group: &QueueGroup<B, Graphics>,
+) -> Frame<B> {
+    Frame {
+        acquire_semaphore: device.create_semaphore(),
+        render_semaphore: device.create_semaphore(),
+        fence: device.create_fence(true),
+        command_pool: device.create_command_pool_typed(
+            graphics_queue_group,
+            CommandPoolCreateFlags::TRANSIENT,
+            1,
+        ),
+    }
+}
+
+fn destroy_frame<B: Backend>(device: &<B as gfx_hal::Backend>::Device, frame: Frame<B>) {
+    device.destroy_command_pool(frame.command_pool.into_raw());
+    device.destroy_fence(frame.fence);
+    device.destroy_semaphore(frame.render_semaphore);
+    device.destroy_semaphore(frame.acquire_semaphore);
+}
+
+/// The events sent from the window thread to the main thread.
+enum WindowEvent {
+    Input(Input),
+    /// The number of physical pixels per logical pixel changed, for example because the window
+    /// moved to a different monitor.
+    HiDpiFactorChanged(f64),
+}
+
+/// When the main loop updates the GUI and draws a frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LoopPolicy {
+    /// After each batch of input events, and otherwise waits for input.
+    EventDriven,
+    /// Once per period, handling the input events received since the last frame.
+    FixedRate(Duration),
+    /// After each batch of input events, or once the timeout has passed since the last frame
+    /// without any.
+    WaitTimeout(Duration),
+}
+
+impl Default for LoopPolicy {
+    fn default() -> LoopPolicy {
+        LoopPolicy::EventDriven
+    }
+}
+
+/// Parses `event`, `fixed:FPS` or `wait:MILLISECONDS`.
+fn parse_loop_policy(policy: &str) -> Option<LoopPolicy> {
+    let mut parts = policy.splitn(2, ':');
+
+    match (parts.next(), parts.next()) {
+        (Some("event"), None) => Some(LoopPolicy::EventDriven),
+        (Some("fixed"), Some(fps)) => fps
+            .parse::<u32>()
+            .ok()
+            .filter(|&fps| fps > 0)
+            .map(|fps| LoopPolicy::FixedRate(Duration::from_secs(1) / fps)),
+        (Some("wait"), Some(milliseconds)) => milliseconds
+            .parse()
+            .ok()
+            .map(|milliseconds| LoopPolicy::WaitTimeout(Duration::from_millis(milliseconds))),
+        _ => None,
+    }
+}
+
+/// Command line options.
+#[derive(Debug, Default)]
+struct Options {
+    /// Seeds the random number generator that moves the ball, so that the same button presses
+    /// give the same frames.
+    seed: Option<u64>,
+    /// Records every input to this file.
+    record: Option<PathBuf>,
+    /// Replays the inputs recorded in this file, at the times they were recorded.
+    replay: Option<PathBuf>,
+    loop_policy: LoopPolicy,
+    /// Which adapter to render with, overriding the environment variable.
+    adapter: Option<AdapterPreference>,
+    /// Lists the adapters and exits, instead of running the demo.
+    list_adapters: bool,
+    /// How frames are synchronized with the display, until F2 switches to the next policy.
+    present_policy: PresentPolicy,
+}
+
+fn parse_options() -> Options {
+    let mut options = Options::default();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                options.seed = Some(
+                    args.next()
+                        .and_then(|seed| seed.parse().ok())
+                        .unwrap_or_else(|| usage_error("--seed requires an integer")),
+                )
+            }
+            "--record" => {
+                options.record = Some(
+                    args.next()
+                        .map(PathBuf::from)
+                        .unwrap_or_else(|| usage_error("--record requires a file")),
+                )
+            }
+            "--replay" => {
+                options.replay = Some(
+                    args.next()
+                        .map(PathBuf::from)
+                        .unwrap_or_else(|| usage_error("--replay requires a file")),
+                )
+            }
+            "--loop" => {
+                options.loop_policy = args
+                    .next()
+                    .and_then(|policy| parse_loop_policy(&policy))
+                    .unwrap_or_else(|| {
+                        usage_error("--loop requires event, fixed:FPS or wait:MILLISECONDS")
+                    })
+            }
+            "--adapter" => {
+                options.adapter = Some(
+                    args.next()
+                        .ok_or_else(|| "--adapter requires a preference".to_string())
+                        .and_then(|preference| {
+                            preference.parse().map_err(|error| format!("{}", error))
+                        })
+                        .unwrap_or_else(|message| usage_error(&message)),
+                )
+            }
+            "--list-adapters" => options.list_adapters = true,
+            "--present" => {
+                options.present_policy = args
+                    .next()
+                    .ok_or_else(|| "--present requires a policy".to_string())
+                    .and_then(|policy| policy.parse().map_err(|error| format!("{}", error)))
+                    .unwrap_or_else(|message| usage_error(&message))
+            }
+            _ => usage_error(&format!("Unknown argument {}", arg)),
+        }
+    }
+
+    options
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!(
+        "Usage: demo [--seed SEED] [--record FILE] [--replay FILE] \
+         [--loop event|fixed:FPS|wait:MILLISECONDS] \
+         [--adapter discrete|integrated|name:NAME|id:VENDOR:DEVICE] [--list-adapters] \
+         [--present vsync|no-vsync|low-latency|power-saving]"
+    );
+    eprintln!(
+        "The adapter may also be set with the {} environment variable.",
+        ADAPTER_ENV_VAR
+    );
+    process::exit(2);
+}
+
+/// Inputs from a recording, handed out at the times they were recorded.
+struct Replay {
+    start: Instant,
+    inputs: VecDeque<TimedInput>,
+}
+
+impl Replay {
+    fn new(inputs: Vec<TimedInput>) -> Replay {
+        Replay {
+            start: Instant::now(),
+            inputs: inputs.into(),
+        }
+    }
+
+    /// The time until the next input is due, or `None` if every input has been handed out.
+    fn time_until_next(&self) -> Option<Duration> {
+        let elapsed = self.start.elapsed();
+
+        self.inputs.front().map(|timed_input| {
+            if timed_input.time > elapsed {
+                timed_input.time - elapsed
+            } else {
+                Duration::from_secs(0)
+            }
+        })
+    }
+
+    /// Removes and returns the inputs that are due.
+    fn take_due(&mut self) -> Vec<Input> {
+        let elapsed = self.start.elapsed();
+        let mut due = Vec::new();
+
+        while self
+            .inputs
+            .front()
+            .map_or(false, |timed_input| timed_input.time <= elapsed)
+        {
+            due.push(self.inputs.pop_front().unwrap().input);
+        }
+
+        due
+    }
+}
+
+fn main() {
+    let options = parse_options();
+
+    if let Err(error) = run(options) {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+/// Runs the demo until its window is closed.
+fn run(options: Options) -> Result<(), Error> {
+    let adapter_preference = options.adapter.clone().unwrap_or_else(|| {
+        env::var(ADAPTER_ENV_VAR)
+            .ok()
+            .map(|preference| {
+                preference.parse().unwrap_or_else(|error| {
+                    eprintln!("Invalid {}: {}", ADAPTER_ENV_VAR, error);
+                    process::exit(2);
+                })
+            })
+            .unwrap_or_default()
+    });
+
+    let mut recorder = options.record.map(|path| {
+        let file = File::create(&path).unwrap_or_else(|error| {
+            eprintln!("Failed to create {}: {}", path.display(), error);
+            process::exit(1);
+        });
+
+        Recorder::new(BufWriter::new(file))
+    });
+
+    let mut replay = options.replay.map(|path| {
+        let inputs = File::open(&path)
+            .map_err(From::from)
+            .and_then(|file| read_recording(BufReader::new(file)))
+            .unwrap_or_else(|error| {
+                eprintln!("Failed to read {}: {}", path.display(), error);
+                process::exit(1);
+            });
+
+        Replay::new(inputs)
+    });
+
+    let (events_sender, events_receiver) = channel();
+
+    let (window_thread, (window, instance, mut surface)) = {
+        let instance = gfx_backend::Instance::create("conrod gfx-ll example", 0);
+
+        if options.list_adapters {
+            for (index, adapter) in instance.enumerate_adapters().iter().enumerate() {
+                println!("{}: {}", index, describe_adapter(&adapter.info));
+            }
+
+            return Ok(());
+        }
+
+        let (window_instance_surface_sender, window_instance_surface_receiver) = channel();
+
+        let window_thread = thread::spawn(move || {
+            let mut events_loop = winit::EventsLoop::new();
+
+            let window = match winit::WindowBuilder::new()
+                .with_dimensions(winit::dpi::LogicalSize::new(WIDTH as f64, HEIGHT as f64))
+                .with_title("Conrod gfx-ll example")
+                .build(&events_loop)
+            {
+                Ok(window) => Arc::new(window),
+                Err(error) => {
+                    // The main thread is waiting for the window, and reports the error.
+                    let _ = window_instance_surface_sender
+                        .send(Err(Error::Window(format!("{}", error))));
+                    return;
+                }
+            };
+
+            let surface = instance.create_surface(&window);
+
+            let _ = window_instance_surface_sender.send(Ok((
+                Arc::downgrade(&window),
+                instance,
+                surface,
+            )));
+
+            // Stops the events loop once the main thread has stopped receiving events.
+            let send = |event| match events_sender.send(event) {
+                Ok(()) => winit::ControlFlow::Continue,
+                Err(_) => winit::ControlFlow::Break,
+            };
+
+            events_loop.run_forever(|event| match event {
+                winit::Event::WindowEvent {
+                    event: winit::WindowEvent::CloseRequested,
+                    ..
+                } => winit::ControlFlow::Break,
+                winit::Event::WindowEvent {
+                    event: winit::WindowEvent::HiDpiFactorChanged(dpi_factor),
+                    ..
+                } => send(WindowEvent::HiDpiFactorChanged(dpi_factor)),
+                event => match conrod::backend::winit::convert_event(event, &*window) {
+                    Some(conrod_event) => send(WindowEvent::Input(conrod_event)),
+                    None => winit::ControlFlow::Continue,
+                },
+            });
+        });
+
+        // The window thread only stops without sending anything if it panics.
+        let window_instance_surface = window_instance_surface_receiver
+            .recv()
+            .unwrap_or_else(|_| Err(Error::Window("The window thread stopped".to_string())))?;
+
+        (window_thread, window_instance_surface)
+    };
+
+    // Only adapters that can present to the window are usable.
+    let mut adapter = select_adapter(
+        instance.enumerate_adapters(),
+        &adapter_preference,
+        |adapter| {
+            adapter
+                .queue_families
+                .iter()
+                .any(|family| surface.supports_queue_family(family))
+        },
+    )?;
+
+    let (surface_capabilities, surface_formats, _) =
+        surface.compatibility(&adapter.physical_device);
+
+    let mut surface_format = choose_surface_format(surface_formats);
+
+    // The present mode is chosen each time the swapchain is built, from the modes the surface
+    // supports then.
+    let mut present_policy = options.present_policy;
+
+    let graphics_queue_family = adapter
+        .queue_families
+        .iter()
+        .find(|family| surface.supports_queue_family(family))
+        .ok_or(Error::NoCompatibleQueueFamily)?;
+
+    let Gpu { device, mut queues } = adapter
+        .physical_device
+        .open(&[(&graphics_queue_family, &[1.0])])?;
+
+    let device = Arc::new(device);
+
+    let mut graphics_queue_group = queues
+        .take::<Graphics>(graphics_queue_family.id())
+        .ok_or(Error::NoCompatibleQueueFamily)?;
+
+    let mut renderer = Renderer::<gfx_backend::Backend>::new(
+        device.clone(),
+        &adapter.physical_device,
+        &graphics_queue_group,
+        build_render_pass::<gfx_backend::Backend>(&device, surface_format)?,
+        Extent2D {
+            width: WIDTH as u32,
+            height: HEIGHT as u32,
+        },
+        AlphaMode::Straight,
+        FRAMES_IN_FLIGHT,
+    )?;
+
+    // The swapchain is built at the start of the main loop, and is `None` while the window has no
+    // area, for example because it is minimized.
+    let mut swapchain_target = None;
+    let mut needs_rebuild = true;
+
+    let mut frames: Vec<_> = (0..FRAMES_IN_FLIGHT)
+        .map(|_| build_frame::<gfx_backend::Backend>(&device, &graphics_queue_group))
+        .collect();
+
+    let mut frame_index = 0;
+
+    let rust_logo = renderer.load_texture(
+        &mut graphics_queue_group.queues[0],
+        include_bytes!("rust.png"),
+    )?;
+
+    let gui::Demo {
+        mut ui,
+        ids,
+        mut state,
+        mut image_map,
+    } = gui::Demo::new([WIDTH as f64, HEIGHT as f64], rust_logo, options.seed);
+
+    // The number of physical pixels per logical pixel. The window and the `Ui` are sized in
+    // logical pixels, and the swapchain in physical pixels.
+    let mut dpi_factor = window
+        .upgrade()
+        .map(|window| window.get_hidpi_factor())
+        .unwrap_or(1.0);
+
+    renderer.set_clear_color(ui.theme.background_color);
+
+    let loop_policy = options.loop_policy;
+
+    // When the next frame is due regardless of input, unless the loop is event driven.
+    let mut next_tick = Instant::now();
+
+    // When the GUI was last updated, for advancing its animations.
+    let mut last_update = Instant::now();
+
+    'main: loop {
+        if needs_rebuild {
+            needs_rebuild = false;
+
+            device.wait_idle()?;
+
+            if let Some(target) = swapchain_target.take() {
+                destroy_swapchain_target::<gfx_backend::Backend>(&device, target);
+            }
+
+            let (_, new_surface_formats, present_modes) =
+                surface.compatibility(&adapter.physical_device);
+            let new_surface_format = choose_surface_format(new_surface_formats);
+            let present_mode = present_policy.choose(&present_modes);
+
+            if new_surface_format != surface_format {
+                surface_format = new_surface_format;
+                renderer.set_render_pass(build_render_pass::<gfx_backend::Backend>(
+                    &device,
+                    surface_format,
+                )?)?;
+            }
+
+            // The window is gone once it has been closed, in which case the events channel is
+            // about to disconnect and end the loop.
+            swapchain_target = match window.upgrade() {
+                Some(window) => build_swapchain_target::<gfx_backend::Backend>(
+                    &window,
+                    &adapter.physical_device,
+                    &device,
+                    &mut surface,
+                    surface_format,
+                    present_mode,
+                    renderer.render_pass(),
+                )?,
+                None => None,
+            };
+
+            if let Some(ref target) = swapchain_target {
+                renderer.resize(target.extent);
+
+                ui.handle_event(Input::Resize(
+                    target.extent.width as f64 / dpi_factor,
+                    target.extent.height as f64 / dpi_factor,
+                ));
+                ui.needs_redraw();
+            }
+        }
+
+        let now = Instant::now();
+        let delta = now - last_update;
+        last_update = now;
+
+        gui::render(&mut ui.set_widgets(), &ids, &mut state, delta);
+
+        if let Some(ref target) = swapchain_target {
+            if let Some(primitives) = ui.draw_if_changed() {
+                let frame = &mut frames[frame_index];
+
+                // The frame's semaphores and command pool, and the renderer's buffers for this
+                // frame, may still be in use by the last submission that used this frame.
+                device.wait_for_fence(&frame.fence, !0);
+
+                // Wait as long as it takes for an image, since the primitives are only returned
+                // once and the frame would otherwise be lost until the `Ui` changes again.
+                let acquired = target
+                    .swapchain
+                    .acquire_image(!0, FrameSync::Semaphore(&frame.acquire_semaphore));
+
+                needs_rebuild = match acquired {
+                    Ok(swapchain_image_index) => {
+                        device.reset_fence(&frame.fence);
+                        frame.command_pool.reset();
+
+                        renderer.fill(primitives, &image_map, dpi_factor)?;
+
+                        let submit = {
+                            let mut command_buffer =
+                                frame.command_pool.acquire_command_buffer::<OneShot>(false);
+
+                            renderer.draw(
+                                &mut command_buffer,
+                                &target.framebuffers[swapchain_image_index as usize],
+                                &image_map,
+                            )?;
+
+                            command_buffer.finish()
+                        };
+
+                        let submission = Submission::new()
+                            .wait_on(&[(
+                                &frame.acquire_semaphore,
+                                PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                            )])
+                            .signal(&[&frame.render_semaphore])
+                            .submit(Some(submit));
+
+                        graphics_queue_group.queues[0].submit(submission, Some(&frame.fence));
+
+                        frame_index = (frame_index + 1) % FRAMES_IN_FLIGHT;
+
+                        graphics_queue_group.queues[0]
+                            .present(
+                                vec![(&target.swapchain, swapchain_image_index)],
+                                vec![&frame.render_semaphore],
+                            )
+                            .is_err()
+                    }
+                    Err(AcquireError::NotReady) => false,
+                    Err(_) => true,
+                };
+            }
+        }
+
+        if needs_rebuild {
+            continue;
+        }
+
+        let replay_timeout = replay.as_ref().and_then(Replay::time_until_next);
+
+        let mut window_event_option = match loop_policy {
+            LoopPolicy::FixedRate(period) => {
+                // Input received before the tick waits for it, so that frames stay evenly spaced.
+                let now = Instant::now();
+
+                if next_tick > now {
+                    thread::sleep(next_tick - now);
+                }
+
+                next_tick += period;
+
+                // Don't try to catch up on ticks missed while a frame took too long.
+                if next_tick < Instant::now() {
+                    next_tick = Instant::now() + period;
+                }
+
+                match events_receiver.try_recv() {
+                    Result::Ok(event) => Some(event),
+                    Result::Err(TryRecvError::Empty) => None,
+                    Result::Err(TryRecvError::Disconnected) => break 'main,
+                }
+            }
+            LoopPolicy::EventDriven | LoopPolicy::WaitTimeout(_) => {
+                let tick_timeout = match loop_policy {
+                    LoopPolicy::WaitTimeout(_) => {
+                        let now = Instant::now();
+                        Some(if next_tick > now {
+                            next_tick - now
+                        } else {
+                            Duration::from_secs(0)
+                        })
+                    }
+                    _ => None,
+                };
+
+                // Wake for whichever of the tick and the next replayed input is due first.
+                let timeout = tick_timeout.into_iter().chain(replay_timeout).min();
+
+                let event = match timeout {
+                    Some(timeout) => match events_receiver.recv_timeout(timeout) {
+                        Result::Ok(event) => Some(event),
+                        Result::Err(RecvTimeoutError::Timeout) => None,
+                        Result::Err(RecvTimeoutError::Disconnected) => break 'main,
+                    },
+                    None => match events_receiver.recv() {
+                        Result::Ok(event) => Some(event),
+                        Result::Err(_) => break 'main,
+                    },
+                };
+
+                if let LoopPolicy::WaitTimeout(timeout) = loop_policy {
+                    next_tick = Instant::now() + timeout;
+                }
+
+                event
+            }
+        };
+
+        // Animations change the GUI without any input, so every frame is drawn unless the loop
+        // only wakes for input.
+        if loop_policy != LoopPolicy::EventDriven {
+            ui.needs_redraw();
+        }
+
+        let replayed_events = replay.as_mut().map(Replay::take_due).unwrap_or_default();
+
+        for event in replayed_events {
+            if is_present_policy_switch(&event) {
+                present_policy = switch_present_policy(present_policy);
+                needs_rebuild = true;
+            }
+
+            needs_rebuild |= is_resize(&event);
+            handle_event(&mut ui, recorder.as_mut(), event);
+        }
+
+        while let Some(window_event) = window_event_option {
+            match window_event {
+                WindowEvent::Input(event) => {
+                    if is_present_policy_switch(&event) {
+                        present_policy = switch_present_policy(present_policy);
+                        needs_rebuild = true;
+                    }
+
+                    needs_rebuild |= is_resize(&event);
+                    handle_event(&mut ui, recorder.as_mut(), event);
+                }
+                WindowEvent::HiDpiFactorChanged(new_dpi_factor) => {
+                    if new_dpi_factor != dpi_factor {
+                        dpi_factor = new_dpi_factor;
+
+                        // Glyphs rasterized at the old scale won't be used again.
+                        renderer.clear_glyph_cache();
+                        needs_rebuild = true;
+                    }
+                }
+            }
+
+            window_event_option = events_receiver.try_recv().ok();
+        }
+    }
+
+    device.wait_idle()?;
+
+    for frame in frames {
+        destroy_frame::<gfx_backend::Backend>(&device, frame);
+    }
+
+    let image_ids: Vec<_> = image_map.keys().cloned().collect();
+
+    for image_id in image_ids {
+        if let Some(texture) = image_map.remove(&image_id) {
+            renderer.destroy_texture(texture);
+        }
+    }
+
+    if let Some(target) = swapchain_target {
+        destroy_swapchain_target::<gfx_backend::Backend>(&device, target);
+    }
+
+    renderer.destroy();
+
+    window_thread.join().unwrap();
+
+    Ok(())
+}
+
+/// Passes `event` to `ui`, first recording it if a recording is in progress.
+fn handle_event(
+    ui: &mut conrod::Ui,
+    recorder: Option<&mut Recorder<BufWriter<File>>>,
+    event: Input,
+) {
+    if let Some(recorder) = recorder {
+        if let Err(error) = recorder.record(&event) {
+            eprintln!("Failed to record input: {}", error);
+        }
+    }
+
+    ui.handle_event(event);
+}
+
+fn is_resize(event: &Input) -> bool {
+    match *event {
+        Input::Resize(..) => true,
+        _ => false,
+    }
+}
+
+/// Whether `event` is a press of F2, which switches to the next present policy.
+fn is_present_policy_switch(event: &Input) -> bool {
+    match *event {
+        Input::Press(Button::Keyboard(Key::F2)) => true,
+        _ => false,
+    }
+}
+
+fn switch_present_policy(present_policy: PresentPolicy) -> PresentPolicy {
+    let next_policy = present_policy.next();
+    println!("Present policy: {:?}", next_policy);
+    next_policy
+}
+
+fn choose_surface_format(surface_formats: Option<Vec<Format>>) -> Format {
+    surface_formats
+        .map(|formats| {
+            formats
+                .iter()
+                .find(|format| format.base_format().1 == ChannelType::Srgb)
+                .map(|format| *format)
+                .unwrap_or(formats[0])
+        })
+        .unwrap_or(Format::Rgb8Srgb)
+}
+
+/// A swapchain along with a framebuffer for each of its images.
+struct SwapchainTarget<B: Backend> {
+    swapchain: <B as gfx_hal::Backend>::Swapchain,
+    image_views: Vec<<B as gfx_hal::Backend>::ImageView>,
+    framebuffers: Vec<<B as gfx_hal::Backend>::Framebuffer>,
+    extent: Extent2D,
+}
+
+/// Builds a swapchain that fills `window`, or returns `None` if the window has no area or has
+/// been closed.
+fn build_swapchain_target<B: Backend>(
+    window: &winit::Window,
+    physical_device: &<B as gfx_hal::Backend>::PhysicalDevice,
+    device: &<B as gfx_hal::Backend>::Device,
+    surface: &mut <B as gfx_hal::Backend>::Surface,
+    surface_format: Format,
+    present_mode: PresentMode,
+    render_pass: &<B as gfx_hal::Backend>::RenderPass,
+) -> Result<Option<SwapchainTarget<B>>, Error> {
+    let (swapchain, backbuffer, extent) = match build_swapchain::<B>(
+        window,
+        physical_device,
+        device,
+        surface,
+        surface_format,
+        present_mode,
+    )? {
+        Some(swapchain) => swapchain,
+        None => return Ok(None),
+    };
+
+    let (image_views, framebuffers) = match build_framebuffers::<B>(
+        device,
+        render_pass,
+        backbuffer,
+        surface_format,
+        extent.to_extent(),
+    ) {
+        Ok(image_views_and_framebuffers) => image_views_and_framebuffers,
+        Err(error) => {
+            device.destroy_swapchain(swapchain);
+            return Err(error);
+        }
+    };
+
+    Ok(Some(SwapchainTarget {
+        swapchain,
+        image_views,
+        framebuffers,
+        extent,
+    }))
+}
+
+fn destroy_swapchain_target<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    target: SwapchainTarget<B>,
+) {
+    destroy_framebuffers::<B>(device, target.image_views, target.framebuffers);
+    device.destroy_swapchain(target.swapchain);
+}
+
+fn build_framebuffers<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    render_pass: &<B as gfx_hal::Backend>::RenderPass,
+    backbuffer: Backbuffer<B>,
+    format: Format,
+    extent: Extent,
+) -> Result<
+    (
+        Vec<<B as gfx_hal::Backend>::ImageView>,
+        Vec<<B as gfx_hal::Backend>::Framebuffer>,
+    ),
+    Error,
+> {
+    match backbuffer {
+        Backbuffer::Images(images) => {
+            let mut image_views = Vec::with_capacity(images.len());
+            let mut framebuffers = Vec::with_capacity(images.len());
+
+            for image in images {
+                match build_framebuffer::<B>(device, render_pass, &image, format, extent) {
+                    Ok((image_view, framebuffer)) => {
+                        image_views.push(image_view);
+                        framebuffers.push(framebuffer);
+                    }
+                    Err(error) => {
+                        destroy_framebuffers::<B>(device, image_views, framebuffers);
+                        return Err(error);
+                    }
+                }
+            }
+
+            Ok((image_views, framebuffers))
+        }
+        Backbuffer::Framebuffer(framebuffer) => Ok((vec![], vec![framebuffer])),
+    }
+}
+
+fn destroy_framebuffers<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    image_views: Vec<<B as gfx_hal::Backend>::ImageView>,
+    framebuffers: Vec<<B as gfx_hal::Backend>::Framebuffer>,
+) {
+    for framebuffer in framebuffers {
+        device.destroy_framebuffer(framebuffer);
+    }
+
+    for image_view in image_views {
+        device.destroy_image_view(image_view);
+    }
+}
+
+fn build_framebuffer<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    render_pass: &<B as gfx_hal::Backend>::RenderPass,
+    image: &<B as gfx_hal::Backend>::Image,
+    format: Format,
+    extent: Extent,
+) -> Result<
+    (
+        <B as gfx_hal::Backend>::ImageView,
+        <B as gfx_hal::Backend>::Framebuffer,
+    ),
+    Error,
+> {
+    let image_view = device
+        .create_image_view(
+            image,
+            ViewKind::D2,
+            format,
+            Swizzle::NO,
+            SubresourceRange {
+                aspects: Aspects::COLOR,
+                levels: 0..1,
+                layers: 0..1,
+            },
+        )
+        .map_err(|error| Error::Swapchain(format!("{:?}", error)))?;
+
+    let framebuffer = {
+        let attachments = vec![&image_view];
+
+        device.create_framebuffer(render_pass, attachments, extent)
+    };
+
+    match framebuffer {
+        Ok(framebuffer) => Ok((image_view, framebuffer)),
+        Err(error) => {
+            device.destroy_image_view(image_view);
+            Err(Error::Swapchain(format!("{:?}", error)))
+        }
+    }
+}
+
+fn build_swapchain<B: Backend>(
+    window: &winit::Window,
+    physical_device: &<B as gfx_hal::Backend>::PhysicalDevice,
+    device: &<B as gfx_hal::Backend>::Device,
+    surface: &mut <B as gfx_hal::Backend>::Surface,
+    surface_format: Format,
+    present_mode: PresentMode,
+) -> Result<Option<(<B as gfx_hal::Backend>::Swapchain, Backbuffer<B>, Extent2D)>, Error> {
+    let (capabilities, _, _) = surface.compatibility(physical_device);
+
+    let extent = match capabilities.current_extent {
+        Some(extent) => extent,
+        None => {
+            let window_size = match window.get_inner_size() {
+                Some(window_size) => window_size.to_physical(window.get_hidpi_factor()),
+                // The window has been closed.
+                None => return Ok(None),
+            };
+            let mut extent = Extent2D {
+                width: window_size.width as _,
+                height: window_size.height as _,
+            };
+            extent.width = extent
+                .width
+                .max(capabilities.extents.start.width)
+                .min(capabilities.extents.end.width);
+            extent.height = extent
+                .height
+                .max(capabilities.extents.start.height)
+                .min(capabilities.extents.end.height);
+            extent
+        }
+    };
+
+    // A swapchain can't have images with no area, as when the window is minimized.
+    if extent.width == 0 || extent.height == 0 {
+        return Ok(None);
+    }
+
+    let image_count = 2.max(capabilities.image_count.start)
+        .min(capabilities.image_count.end);
+
+    let config = SwapchainConfig::new(extent.width, extent.height, surface_format, image_count)
+        .with_image_usage(Usage::COLOR_ATTACHMENT)
+        .with_mode(present_mode);
+
+    let (swapchain, backbuffer) = device.create_swapchain(surface, config, None);
+    
+    Ok(Some((swapchain, backbuffer, extent)))
+}
@@ -0,0 +1,220 @@
+//! Renders the demo GUI without a window and saves it as a PNG.
+//!
+//! Usage: `headless [--adapter PREFERENCE] [--list-adapters] [WIDTH HEIGHT] [OUTPUT]`. Any
+//! Vulkan implementation will do, including a software one on a machine with no display, which
+//! can be picked with `--adapter name:NAME` or the `CONROD_GFX_ADAPTER` environment variable.
+
+#[macro_use]
+extern crate conrod;
+#[cfg(all(windows, not(feature = "vulkan")))]
+extern crate gfx_backend_dx12 as gfx_backend;
+#[cfg(target_os = "macos")]
+extern crate gfx_backend_metal as gfx_backend;
+#[cfg(any(all(not(windows), not(target_os = "macos")), all(windows, feature = "vulkan")))]
+extern crate gfx_backend_vulkan as gfx_backend;
+extern crate conrod_gfx_ll;
+extern crate gfx_hal;
+extern crate image;
+extern crate rand;
+extern crate rusttype;
+
+#[path = "../demo/gui.rs"]
+mod gui;
+#[path = "../demo/theme.rs"]
+mod theme;
+
+use conrod_gfx_ll::adapter::{describe_adapter, select_adapter, ADAPTER_ENV_VAR};
+use conrod_gfx_ll::{AdapterPreference, AlphaMode, Error, OffscreenRenderer};
+use gfx_hal::device::Device;
+use gfx_hal::queue::capability::Graphics;
+use gfx_hal::queue::family::QueueFamily;
+use gfx_hal::window::Extent2D;
+use gfx_hal::Gpu;
+use gfx_hal::Instance;
+use gfx_hal::PhysicalDevice;
+use std::env;
+use std::process;
+use std::sync::Arc;
+
+const DEFAULT_WIDTH: u32 = 600;
+const DEFAULT_HEIGHT: u32 = 420;
+const DEFAULT_OUTPUT: &str = "headless.png";
+
+fn main() {
+    let mut adapter_preference = None;
+    let mut list_adapters = false;
+    let mut args = Vec::new();
+
+    let mut all_args = env::args().skip(1);
+
+    while let Some(arg) = all_args.next() {
+        match arg.as_str() {
+            "--adapter" => {
+                adapter_preference = Some(parse_adapter_preference(
+                    &all_args
+                        .next()
+                        .unwrap_or_else(|| usage_error("--adapter requires a preference")),
+                ))
+            }
+            "--list-adapters" => list_adapters = true,
+            _ => args.push(arg),
+        }
+    }
+
+    let adapter_preference = adapter_preference.unwrap_or_else(|| {
+        env::var(ADAPTER_ENV_VAR)
+            .ok()
+            .map(|preference| parse_adapter_preference(&preference))
+            .unwrap_or_default()
+    });
+
+    let (extent, output) = match args.len() {
+        0 | 1 => (
+            Extent2D {
+                width: DEFAULT_WIDTH,
+                height: DEFAULT_HEIGHT,
+            },
+            args.get(0).map(String::as_str).unwrap_or(DEFAULT_OUTPUT),
+        ),
+        _ => (
+            Extent2D {
+                width: parse_dimension(&args[0], "WIDTH"),
+                height: parse_dimension(&args[1], "HEIGHT"),
+            },
+            args.get(2).map(String::as_str).unwrap_or(DEFAULT_OUTPUT),
+        ),
+    };
+
+    let instance = gfx_backend::Instance::create("conrod gfx-ll headless example", 0);
+
+    if list_adapters {
+        for (index, adapter) in instance.enumerate_adapters().iter().enumerate() {
+            println!("{}: {}", index, describe_adapter(&adapter.info));
+        }
+
+        return;
+    }
+
+    let pixels = render_demo(&instance, &adapter_preference, extent).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+
+    image::save_buffer(
+        output,
+        &pixels,
+        extent.width,
+        extent.height,
+        image::ColorType::RGBA(8),
+    )
+    .unwrap_or_else(|error| {
+        eprintln!("Failed to save {}: {}", output, error);
+        process::exit(1);
+    });
+}
+
+/// Renders the demo GUI with the adapter that best fits `adapter_preference`, returning its
+/// pixels.
+fn render_demo(
+    instance: &gfx_backend::Instance,
+    adapter_preference: &AdapterPreference,
+    extent: Extent2D,
+) -> Result<Vec<u8>, Error> {
+    let mut adapter = select_adapter(
+        instance.enumerate_adapters(),
+        adapter_preference,
+        |adapter| {
+            adapter
+                .queue_families
+                .iter()
+                .any(|family| family.supports_graphics())
+        },
+    )?;
+
+    let graphics_queue_family = adapter
+        .queue_families
+        .iter()
+        .find(|family| family.supports_graphics())
+        .ok_or(Error::NoCompatibleQueueFamily)?;
+
+    let Gpu { device, mut queues } = adapter
+        .physical_device
+        .open(&[(&graphics_queue_family, &[1.0])])?;
+
+    let device = Arc::new(device);
+
+    let mut graphics_queue_group = queues
+        .take::<Graphics>(graphics_queue_family.id())
+        .ok_or(Error::NoCompatibleQueueFamily)?;
+
+    let mut offscreen_renderer = OffscreenRenderer::<gfx_backend::Backend>::new(
+        device.clone(),
+        &adapter.physical_device,
+        &graphics_queue_group,
+        extent,
+        AlphaMode::Straight,
+    )?;
+
+    let rust_logo = offscreen_renderer.renderer().load_texture(
+        &mut graphics_queue_group.queues[0],
+        include_bytes!("../demo/rust.png"),
+    )?;
+
+    let mut demo = gui::Demo::new(
+        [extent.width as f64, extent.height as f64],
+        rust_logo,
+        None,
+    );
+
+    offscreen_renderer
+        .renderer()
+        .set_clear_color(demo.ui.theme.background_color);
+
+    let pixels = offscreen_renderer.render(
+        &mut graphics_queue_group.queues[0],
+        demo.ui.draw(),
+        &demo.image_map,
+        1.0,
+    )?;
+
+    device.wait_idle()?;
+
+    let image_ids: Vec<_> = demo.image_map.keys().cloned().collect();
+
+    for image_id in image_ids {
+        if let Some(texture) = demo.image_map.remove(&image_id) {
+            offscreen_renderer.renderer().destroy_texture(texture);
+        }
+    }
+
+    offscreen_renderer.destroy();
+
+    Ok(pixels)
+}
+
+fn parse_adapter_preference(preference: &str) -> AdapterPreference {
+    preference
+        .parse()
+        .unwrap_or_else(|error| usage_error(&format!("{}", error)))
+}
+
+fn parse_dimension(dimension: &str, name: &str) -> u32 {
+    dimension
+        .parse()
+        .ok()
+        .filter(|&dimension| dimension > 0)
+        .unwrap_or_else(|| usage_error(&format!("{} must be a positive integer", name)))
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!(
+        "Usage: headless [--adapter discrete|integrated|name:NAME|id:VENDOR:DEVICE] \
+         [--list-adapters] [WIDTH HEIGHT] [OUTPUT]"
+    );
+    eprintln!(
+        "The adapter may also be set with the {} environment variable.",
+        ADAPTER_ENV_VAR
+    );
+    process::exit(2);
+}
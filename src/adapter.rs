@@ -0,0 +1,204 @@
+use gfx_hal::adapter::{AdapterInfo, DeviceType};
+use gfx_hal::{Adapter, Backend};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The environment variable that an `AdapterPreference` is read from when none is given on the
+/// command line.
+pub const ADAPTER_ENV_VAR: &str = "CONROD_GFX_ADAPTER";
+
+/// Which adapter to render with.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AdapterPreference {
+    /// A discrete GPU if there is one, then an integrated one, then anything else.
+    Discrete,
+    /// An integrated GPU if there is one, then a discrete one, then anything else.
+    Integrated,
+    /// Only an adapter whose name contains this, ignoring case.
+    Name(String),
+    /// Only the adapter with this PCI vendor and device id.
+    Id { vendor: usize, device: usize },
+}
+
+impl Default for AdapterPreference {
+    fn default() -> AdapterPreference {
+        AdapterPreference::Integrated
+    }
+}
+
+impl AdapterPreference {
+    /// How well an adapter fits the preference, lower being better, or `None` if it must not be
+    /// used.
+    pub fn rank(&self, info: &AdapterInfo) -> Option<u32> {
+        let type_rank = |preferred: &DeviceType, fallback: &DeviceType| {
+            if info.device_type == *preferred {
+                0
+            } else if info.device_type == *fallback {
+                1
+            } else {
+                match info.device_type {
+                    DeviceType::VirtualGpu => 2,
+                    DeviceType::Cpu => 3,
+                    _ => 4,
+                }
+            }
+        };
+
+        match *self {
+            AdapterPreference::Discrete => Some(type_rank(
+                &DeviceType::DiscreteGpu,
+                &DeviceType::IntegratedGpu,
+            )),
+            AdapterPreference::Integrated => Some(type_rank(
+                &DeviceType::IntegratedGpu,
+                &DeviceType::DiscreteGpu,
+            )),
+            AdapterPreference::Name(ref name) => {
+                if info.name.to_lowercase().contains(&name.to_lowercase()) {
+                    Some(0)
+                } else {
+                    None
+                }
+            }
+            AdapterPreference::Id { vendor, device } => {
+                if info.vendor == vendor && info.device == device {
+                    Some(0)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Parses `discrete`, `integrated`, `name:NAME` or `id:VENDOR:DEVICE`, where the ids are decimal
+/// or hexadecimal with a `0x` prefix.
+impl FromStr for AdapterPreference {
+    type Err = ParseAdapterPreferenceError;
+
+    fn from_str(preference: &str) -> Result<AdapterPreference, ParseAdapterPreferenceError> {
+        let parse_id = |id: &str| {
+            if id.starts_with("0x") || id.starts_with("0X") {
+                usize::from_str_radix(&id[2..], 16).ok()
+            } else {
+                id.parse().ok()
+            }
+        };
+
+        let mut parts = preference.splitn(2, ':');
+
+        match (parts.next(), parts.next()) {
+            (Some("discrete"), None) => Ok(AdapterPreference::Discrete),
+            (Some("integrated"), None) => Ok(AdapterPreference::Integrated),
+            (Some("name"), Some(name)) if !name.is_empty() => {
+                Ok(AdapterPreference::Name(name.to_string()))
+            }
+            (Some("id"), Some(ids)) => {
+                let mut ids = ids.splitn(2, ':');
+
+                match (
+                    ids.next().and_then(&parse_id),
+                    ids.next().and_then(&parse_id),
+                ) {
+                    (Some(vendor), Some(device)) => Ok(AdapterPreference::Id { vendor, device }),
+                    _ => Err(ParseAdapterPreferenceError(preference.to_string())),
+                }
+            }
+            _ => Err(ParseAdapterPreferenceError(preference.to_string())),
+        }
+    }
+}
+
+/// A string that is not a valid `AdapterPreference`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseAdapterPreferenceError(pub String);
+
+impl fmt::Display for ParseAdapterPreferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid adapter preference {:?}, expected discrete, integrated, name:NAME or \
+             id:VENDOR:DEVICE",
+            self.0
+        )
+    }
+}
+
+impl Error for ParseAdapterPreferenceError {}
+
+/// Describes an adapter on one line, with the details that an `AdapterPreference` can match.
+pub fn describe_adapter(info: &AdapterInfo) -> String {
+    format!(
+        "{} (id {:#06x}:{:#06x}, {:?})",
+        info.name, info.vendor, info.device, info.device_type
+    )
+}
+
+/// Picks the adapter that best fits `preference` out of those for which `is_usable` is true, for
+/// example because they have a queue family that can present to a surface. Of equally good
+/// adapters, the first is picked.
+pub fn select_adapter<B: Backend, F: Fn(&Adapter<B>) -> bool>(
+    adapters: Vec<Adapter<B>>,
+    preference: &AdapterPreference,
+    is_usable: F,
+) -> Result<Adapter<B>, AdapterError> {
+    if adapters.is_empty() {
+        return Err(AdapterError::NoAdapters);
+    }
+
+    let available = adapters
+        .iter()
+        .map(|adapter| describe_adapter(&adapter.info))
+        .collect();
+
+    adapters
+        .into_iter()
+        .filter(|adapter| is_usable(adapter))
+        .filter_map(|adapter| preference.rank(&adapter.info).map(|rank| (rank, adapter)))
+        .min_by_key(|&(rank, _)| rank)
+        .map(|(_, adapter)| adapter)
+        .ok_or_else(|| AdapterError::NoMatch {
+            preference: preference.clone(),
+            available,
+        })
+}
+
+/// An error picking an adapter.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AdapterError {
+    /// The backend found no adapters at all.
+    NoAdapters,
+    /// None of the adapters are usable and fit the preference.
+    NoMatch {
+        preference: AdapterPreference,
+        /// Descriptions of every adapter, usable or not.
+        available: Vec<String>,
+    },
+}
+
+impl fmt::Display for AdapterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdapterError::NoAdapters => write!(f, "No adapters were found"),
+            AdapterError::NoMatch {
+                preference,
+                available,
+            } => {
+                write!(
+                    f,
+                    "No usable adapter fits the preference {:?}. Available adapters:",
+                    preference
+                )?;
+
+                for description in available {
+                    write!(f, "\n  {}", description)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for AdapterError {}
@@ -0,0 +1,186 @@
+use error::Error;
+use gfx_hal::buffer::Usage;
+use gfx_hal::device::Device;
+use gfx_hal::memory::Properties;
+use gfx_hal::Backend;
+use gfx_hal::MemoryType;
+use gfx_hal::MemoryTypeId;
+use std::mem::replace;
+use std::ptr::copy_nonoverlapping;
+use std::slice;
+
+/// Finds the first memory type allowed by `type_mask` that has all of `properties`.
+pub fn find_memory_type(
+    memory_types: &[MemoryType],
+    type_mask: u64,
+    properties: Properties,
+) -> Option<MemoryTypeId> {
+    memory_types
+        .iter()
+        .enumerate()
+        .position(|(id, memory_type)| {
+            type_mask & (1 << id) != 0 && memory_type.properties.contains(properties)
+        })
+        .map(MemoryTypeId)
+}
+
+/// A buffer in host-visible memory that the CPU writes into before the GPU reads from it, or
+/// reads from after the GPU has written into it.
+///
+/// The buffer's memory stays mapped for as long as the buffer exists.
+pub struct HostBuffer<B: Backend> {
+    buffer: <B as gfx_hal::Backend>::Buffer,
+    memory: <B as gfx_hal::Backend>::Memory,
+    mapping: *mut u8,
+    mapping_size: u64,
+    /// False if writes through `mapping` must be flushed before the GPU can see them.
+    coherent: bool,
+    usage: Usage,
+    size: u64,
+}
+
+// `mapping` points into `memory`, which the buffer owns, and is only written through a
+// `&mut HostBuffer`, so moving the buffer to another thread along with its memory is sound.
+unsafe impl<B: Backend> Send for HostBuffer<B> {}
+
+impl<B: Backend> HostBuffer<B> {
+    pub fn buffer(&self) -> &<B as gfx_hal::Backend>::Buffer {
+        &self.buffer
+    }
+
+    /// The number of bytes that fit in the buffer before it has to grow.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Builds a buffer with room for `size` bytes in host-visible memory, preferring memory that is
+/// also coherent.
+pub fn build_host_buffer<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    memory_types: &[MemoryType],
+    usage: Usage,
+    size: u64,
+) -> Result<HostBuffer<B>, Error> {
+    let size = size.max(1);
+
+    let unbound_buffer = device.create_buffer(size, usage)?;
+
+    let requirements = device.get_buffer_requirements(&unbound_buffer);
+
+    let (memory_type, coherent) = find_memory_type(
+        memory_types,
+        requirements.type_mask,
+        Properties::CPU_VISIBLE | Properties::COHERENT,
+    )
+    .map(|memory_type| (memory_type, true))
+    .or_else(|| {
+        find_memory_type(
+            memory_types,
+            requirements.type_mask,
+            Properties::CPU_VISIBLE,
+        )
+        .map(|memory_type| (memory_type, false))
+    })
+    .ok_or(Error::NoMemoryType)?;
+
+    let memory = device.allocate_memory(memory_type, requirements.size)?;
+
+    let buffer = match device.bind_buffer_memory(&memory, 0, unbound_buffer) {
+        Ok(buffer) => buffer,
+        Err(error) => {
+            device.free_memory(memory);
+            return Err(error.into());
+        }
+    };
+
+    let mapping = match device.map_memory(&memory, 0..requirements.size) {
+        Ok(mapping) => mapping,
+        Err(error) => {
+            device.destroy_buffer(buffer);
+            device.free_memory(memory);
+            return Err(error.into());
+        }
+    };
+
+    Ok(HostBuffer {
+        buffer,
+        memory,
+        mapping,
+        mapping_size: requirements.size,
+        coherent,
+        usage,
+        size,
+    })
+}
+
+pub fn destroy_host_buffer<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    host_buffer: HostBuffer<B>,
+) {
+    device.unmap_memory(&host_buffer.memory);
+    device.destroy_buffer(host_buffer.buffer);
+    device.free_memory(host_buffer.memory);
+}
+
+/// Copies `data` to the start of `host_buffer`.
+///
+/// If `data` doesn't fit, `host_buffer` is first replaced by a buffer at least twice the size. If
+/// that fails, `host_buffer` is left as it was.
+/// The GPU must have finished reading from `host_buffer`.
+pub fn write_host_buffer<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    memory_types: &[MemoryType],
+    host_buffer: &mut HostBuffer<B>,
+    data: &[u8],
+) -> Result<(), Error> {
+    let size = data.len() as u64;
+
+    if size > host_buffer.size {
+        let new_size = size.next_power_of_two().max(host_buffer.size * 2);
+        let usage = host_buffer.usage;
+
+        let old_host_buffer = replace(
+            host_buffer,
+            build_host_buffer(device, memory_types, usage, new_size)?,
+        );
+
+        destroy_host_buffer(device, old_host_buffer);
+    }
+
+    if size > 0 {
+        unsafe {
+            copy_nonoverlapping(data.as_ptr(), host_buffer.mapping, data.len());
+        }
+
+        // Flushing the whole mapping sidesteps the alignment rules for flushed ranges.
+        if !host_buffer.coherent {
+            device.flush_mapped_memory_ranges(Some((
+                &host_buffer.memory,
+                0..host_buffer.mapping_size,
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Copies the first `size` bytes of `host_buffer`, which must not exceed its size.
+///
+/// The GPU must have finished writing to `host_buffer`.
+pub fn read_host_buffer<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    host_buffer: &HostBuffer<B>,
+    size: u64,
+) -> Vec<u8> {
+    assert!(size <= host_buffer.size);
+
+    if !host_buffer.coherent {
+        device.invalidate_mapped_memory_ranges(Some((
+            &host_buffer.memory,
+            0..host_buffer.mapping_size,
+        )));
+    }
+
+    unsafe { slice::from_raw_parts(host_buffer.mapping, size as usize).to_vec() }
+}
@@ -1,4 +1,31 @@
 #version 450 core
 
+// Must match the MODE_* constants in renderer.rs.
+const uint MODE_TEXT = 0u;
+const uint MODE_IMAGE = 1u;
+
+// Set when the pipeline blends premultiplied alpha.
+layout(constant_id = 0) const bool PREMULTIPLY = false;
+
+layout(set = 0, binding = 0) uniform sampler2D glyph_cache;
+layout(set = 1, binding = 0) uniform sampler2D image;
+
+layout(location = 0) in vec2 v_uv;
+layout(location = 1) in vec4 v_color;
+layout(location = 2) flat in uint v_mode;
+
+layout(location = 0) out vec4 target;
+
 void main(void) {
-}
\ No newline at end of file
+    if (v_mode == MODE_TEXT) {
+        target = vec4(v_color.rgb, v_color.a * texture(glyph_cache, v_uv).r);
+    } else if (v_mode == MODE_IMAGE) {
+        target = texture(image, v_uv) * v_color;
+    } else {
+        target = v_color;
+    }
+
+    if (PREMULTIPLY) {
+        target.rgb *= target.a;
+    }
+}
@@ -1,4 +1,25 @@
 #version 450 core
 
+// Maps vertex positions into normalized device coordinates. The identity transform is used for
+// positions that are already in NDC; pixel-space positions are scaled by 2 / framebuffer size and
+// translated by -1.
+layout(push_constant) uniform Transform {
+    vec2 scale;
+    vec2 translate;
+} transform;
+
+layout(location = 0) in vec2 a_position;
+layout(location = 1) in vec2 a_uv;
+layout(location = 2) in vec4 a_color;
+layout(location = 3) in uint a_mode;
+
+layout(location = 0) out vec2 v_uv;
+layout(location = 1) out vec4 v_color;
+layout(location = 2) flat out uint v_mode;
+
 void main(void) {
-}
\ No newline at end of file
+    v_uv = a_uv;
+    v_color = a_color;
+    v_mode = a_mode;
+    gl_Position = vec4(a_position * transform.scale + transform.translate, 0.0, 1.0);
+}
@@ -0,0 +1,146 @@
+use adapter::AdapterError;
+use gfx_hal::buffer::CreationError as BufferCreationError;
+use gfx_hal::device::{AllocationError, BindError, ShaderError};
+use gfx_hal::error::{DeviceCreationError, HostExecutionError};
+use gfx_hal::format::Format;
+use gfx_hal::image::{CreationError as ImageCreationError, ViewError};
+use gfx_hal::mapping::Error as MappingError;
+use gfx_hal::pso::{
+    AllocationError as DescriptorAllocationError, CreationError as PipelineCreationError,
+};
+use image::ImageError;
+use std::error;
+use std::fmt;
+
+/// An error setting up rendering, or growing the buffers that rendering writes into.
+#[derive(Debug)]
+pub enum Error {
+    /// No adapter is usable.
+    NoAdapter(AdapterError),
+    /// The adapter has no queue family that supports what is needed, such as graphics or
+    /// presenting to a surface.
+    NoCompatibleQueueFamily,
+    /// Opening the logical device failed for a reason other than running out of memory.
+    DeviceCreation(DeviceCreationError),
+    /// Creating the window failed, with the windowing library's description of why.
+    Window(String),
+    /// Creating the swapchain or the views and framebuffers for its images failed.
+    Swapchain(String),
+    /// The format can't be used for a color attachment.
+    UnsupportedFormat(Format),
+    /// The host or the device ran out of memory.
+    OutOfMemory,
+    /// No memory type has the properties that a buffer or image needs.
+    NoMemoryType,
+    /// Creating a buffer, image, shader module or pipeline failed for a reason other than running
+    /// out of memory, with gfx-hal's description of why.
+    ResourceCreation(String),
+    /// Reading or decoding the image for a texture failed.
+    Image(ImageError),
+    /// The device was lost, for example because the GPU was reset or removed.
+    DeviceLost,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoAdapter(error) => write!(f, "{}", error),
+            Error::NoCompatibleQueueFamily => write!(f, "No compatible queue family was found"),
+            Error::DeviceCreation(error) => write!(f, "Failed to open the device: {:?}", error),
+            Error::Window(message) => write!(f, "Failed to create the window: {}", message),
+            Error::Swapchain(message) => write!(f, "Failed to create the swapchain: {}", message),
+            Error::UnsupportedFormat(format) => {
+                write!(f, "{:?} can't be used for a color attachment", format)
+            }
+            Error::OutOfMemory => write!(f, "Out of memory"),
+            Error::NoMemoryType => write!(f, "No suitable memory type was found"),
+            Error::ResourceCreation(message) => {
+                write!(f, "Failed to create a GPU resource: {}", message)
+            }
+            Error::Image(error) => write!(f, "Failed to load the image: {}", error),
+            Error::DeviceLost => write!(f, "The device was lost"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::NoAdapter(error) => Some(error),
+            Error::Image(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<AdapterError> for Error {
+    fn from(error: AdapterError) -> Error {
+        Error::NoAdapter(error)
+    }
+}
+
+impl From<DeviceCreationError> for Error {
+    fn from(error: DeviceCreationError) -> Error {
+        match error {
+            DeviceCreationError::OutOfHostMemory | DeviceCreationError::OutOfDeviceMemory => {
+                Error::OutOfMemory
+            }
+            error => Error::DeviceCreation(error),
+        }
+    }
+}
+
+impl From<HostExecutionError> for Error {
+    fn from(error: HostExecutionError) -> Error {
+        match error {
+            HostExecutionError::OutOfHostMemory | HostExecutionError::OutOfDeviceMemory => {
+                Error::OutOfMemory
+            }
+            HostExecutionError::DeviceLost => Error::DeviceLost,
+        }
+    }
+}
+
+impl From<ImageError> for Error {
+    fn from(error: ImageError) -> Error {
+        Error::Image(error)
+    }
+}
+
+impl From<DescriptorAllocationError> for Error {
+    fn from(error: DescriptorAllocationError) -> Error {
+        match error {
+            DescriptorAllocationError::OutOfHostMemory
+            | DescriptorAllocationError::OutOfDeviceMemory => Error::OutOfMemory,
+            error => Error::ResourceCreation(format!("{:?}", error)),
+        }
+    }
+}
+
+/// Implements `From` for gfx-hal errors that have an `OutOfMemory` variant, mapping that variant
+/// to `Error::OutOfMemory` and the others to `Error::ResourceCreation`.
+macro_rules! impl_from_resource_error {
+    ($($error:ident),*) => {
+        $(
+            impl From<$error> for Error {
+                fn from(error: $error) -> Error {
+                    match error {
+                        $error::OutOfMemory(_) => Error::OutOfMemory,
+                        error => Error::ResourceCreation(format!("{:?}", error)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_resource_error!(
+    AllocationError,
+    BindError,
+    BufferCreationError,
+    ImageCreationError,
+    ViewError,
+    MappingError,
+    ShaderError,
+    PipelineCreationError
+);
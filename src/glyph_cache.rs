@@ -0,0 +1,100 @@
+use conrod::render::Text;
+use conrod::text::{font, GlyphCache};
+use rusttype::gpu_cache::CacheWriteErr;
+use rusttype::Rect;
+use texture::TextureRegion;
+
+/// Glyphs may be reused at scales and subpixel positions within these tolerances of the
+/// originally rasterized glyph.
+const SCALE_TOLERANCE: f32 = 0.1;
+const POSITION_TOLERANCE: f32 = 0.1;
+
+/// The CPU side of the glyph cache: tracks which glyphs have been rasterized where, and which
+/// regions of the glyph cache texture must be updated before the next draw.
+pub struct GlyphAtlas {
+    cache: GlyphCache<'static>,
+    dirty_regions: Vec<TextureRegion>,
+}
+
+impl GlyphAtlas {
+    pub fn new(width: u32, height: u32) -> GlyphAtlas {
+        GlyphAtlas {
+            cache: GlyphCache::builder()
+                .dimensions(width, height)
+                .scale_tolerance(SCALE_TOLERANCE)
+                .position_tolerance(POSITION_TOLERANCE)
+                .build(),
+            dirty_regions: Vec::new(),
+        }
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.cache.dimensions()
+    }
+
+    /// Queues the glyphs of `text` to be rasterized by the next `cache_queued`.
+    pub fn queue_text(&mut self, text: Text, font_id: font::Id, dpi_factor: f32) {
+        for glyph in text.positioned_glyphs(dpi_factor) {
+            self.cache.queue_glyph(font_id.index(), glyph.clone());
+        }
+    }
+
+    /// Rasterizes any queued glyphs that are not already cached.
+    ///
+    /// Glyphs of earlier calls may be evicted to make room, so every glyph that will be looked up
+    /// with `text_rects` must be queued before a single call.
+    pub fn cache_queued(&mut self) {
+        if self.upload_queued().is_err() {
+            // The queued glyphs don't fit around those kept from earlier calls, so start again
+            // with an empty cache. Everything still needed is queued, so it is all rasterized
+            // again and the regions from the failed attempt are no longer needed.
+            self.cache.clear();
+            self.dirty_regions.clear();
+
+            // If the queued glyphs don't fit even in an empty cache, those that don't fit are not
+            // drawn. The queue is cleared so that they aren't retried on every later call.
+            if self.upload_queued().is_err() {
+                self.cache.clear_queue();
+            }
+        }
+    }
+
+    fn upload_queued(&mut self) -> Result<(), CacheWriteErr> {
+        let dirty_regions = &mut self.dirty_regions;
+
+        self.cache.cache_queued(|rect, data| {
+            dirty_regions.push(TextureRegion {
+                x: rect.min.x,
+                y: rect.min.y,
+                width: rect.width(),
+                height: rect.height(),
+                data: data.to_vec(),
+            });
+        })
+    }
+
+    /// Returns the texture coordinates and the screen rectangle, in physical pixels from the top
+    /// left of the window, of each glyph of `text` that has a visible outline and is cached.
+    pub fn text_rects(
+        &self,
+        text: Text,
+        font_id: font::Id,
+        dpi_factor: f32,
+    ) -> Vec<(Rect<f32>, Rect<i32>)> {
+        text.positioned_glyphs(dpi_factor)
+            .iter()
+            .filter_map(|glyph| {
+                self.cache
+                    .rect_for(font_id.index(), glyph)
+                    .ok()
+                    .and_then(|rects| rects)
+            })
+            .collect()
+    }
+
+    /// Returns the regions of the glyph cache texture rasterized since the last call, as R8
+    /// texel data.
+    pub fn take_dirty_regions(&mut self) -> Vec<TextureRegion> {
+        ::std::mem::replace(&mut self.dirty_regions, Vec::new())
+    }
+}
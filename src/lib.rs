@@ -0,0 +1,34 @@
+extern crate conrod;
+extern crate gfx_hal;
+#[macro_use]
+extern crate glsl_to_spirv_macros;
+#[macro_use]
+extern crate glsl_to_spirv_macros_impl;
+extern crate image;
+extern crate rusttype;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+pub mod adapter;
+pub mod buffer;
+pub mod error;
+pub mod glyph_cache;
+pub mod offscreen;
+pub mod playback;
+pub mod present;
+pub mod recording;
+pub mod renderer;
+pub mod software;
+pub mod texture;
+
+pub use adapter::AdapterPreference;
+pub use error::Error;
+pub use offscreen::OffscreenRenderer;
+pub use playback::{Playback, Script};
+pub use present::PresentPolicy;
+pub use recording::Recorder;
+pub use renderer::{AlphaMode, Renderer};
+pub use software::SoftwareRenderer;
+pub use texture::Texture;
@@ -0,0 +1,314 @@
+use buffer::{build_host_buffer, destroy_host_buffer, read_host_buffer, HostBuffer};
+use conrod::image;
+use conrod::render::Primitives;
+use error::Error;
+use gfx_hal::buffer::{Access as BufferAccess, Usage as BufferUsage};
+use gfx_hal::command::{BufferImageCopy, CommandBuffer, OneShot};
+use gfx_hal::device::Device;
+use gfx_hal::format::{Aspects, Format};
+use gfx_hal::image::{Access, Extent, Layout, Offset, SubresourceLayers, SubresourceRange, Usage};
+use gfx_hal::memory::{Barrier, Dependencies};
+use gfx_hal::pool::{CommandPool, CommandPoolCreateFlags};
+use gfx_hal::pso::PipelineStage;
+use gfx_hal::queue::capability::Graphics;
+use gfx_hal::queue::{CommandQueue, QueueGroup, Submission};
+use gfx_hal::window::Extent2D;
+use gfx_hal::Backend;
+use gfx_hal::MemoryType;
+use gfx_hal::PhysicalDevice;
+use renderer::{build_offscreen_render_pass, AlphaMode, Renderer};
+use std::sync::Arc;
+use texture::{build_image, Texture};
+
+/// The format of offscreen color images. Pixels are read back as sRGB-encoded RGBA8, the same
+/// encoding a typical swapchain would present.
+pub const OFFSCREEN_FORMAT: Format = Format::Rgba8Srgb;
+
+const BYTES_PER_PIXEL: u32 = 4;
+
+/// A color image with a framebuffer for drawing into it, and a buffer that its pixels are copied
+/// into so that the CPU can read them.
+pub struct OffscreenTarget<B: Backend> {
+    image: <B as gfx_hal::Backend>::Image,
+    memory: <B as gfx_hal::Backend>::Memory,
+    view: <B as gfx_hal::Backend>::ImageView,
+    pub framebuffer: <B as gfx_hal::Backend>::Framebuffer,
+    readback_buffer: HostBuffer<B>,
+    extent: Extent2D,
+}
+
+impl<B: Backend> OffscreenTarget<B> {
+    pub fn extent(&self) -> Extent2D {
+        self.extent
+    }
+}
+
+/// Builds an offscreen target of size `extent` whose framebuffer is compatible with
+/// `render_pass`, which must have been built by `build_offscreen_render_pass` with
+/// `OFFSCREEN_FORMAT`.
+pub fn build_offscreen_target<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    memory_types: &[MemoryType],
+    render_pass: &<B as gfx_hal::Backend>::RenderPass,
+    extent: Extent2D,
+) -> Result<OffscreenTarget<B>, Error> {
+    let (image, memory, view) = build_image::<B>(
+        device,
+        memory_types,
+        OFFSCREEN_FORMAT,
+        extent.width,
+        extent.height,
+        Usage::COLOR_ATTACHMENT | Usage::TRANSFER_SRC,
+    )?;
+
+    let framebuffer = match device.create_framebuffer(render_pass, vec![&view], extent.to_extent())
+    {
+        Ok(framebuffer) => framebuffer,
+        Err(error) => {
+            device.destroy_image_view(view);
+            device.destroy_image(image);
+            device.free_memory(memory);
+            return Err(Error::ResourceCreation(format!("{:?}", error)));
+        }
+    };
+
+    let readback_buffer = match build_host_buffer(
+        device,
+        memory_types,
+        BufferUsage::TRANSFER_DST,
+        readback_size(extent),
+    ) {
+        Ok(readback_buffer) => readback_buffer,
+        Err(error) => {
+            device.destroy_framebuffer(framebuffer);
+            device.destroy_image_view(view);
+            device.destroy_image(image);
+            device.free_memory(memory);
+            return Err(error);
+        }
+    };
+
+    Ok(OffscreenTarget {
+        image,
+        memory,
+        view,
+        framebuffer,
+        readback_buffer,
+        extent,
+    })
+}
+
+pub fn destroy_offscreen_target<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    target: OffscreenTarget<B>,
+) {
+    destroy_host_buffer(device, target.readback_buffer);
+    device.destroy_framebuffer(target.framebuffer);
+    device.destroy_image_view(target.view);
+    device.destroy_image(target.image);
+    device.free_memory(target.memory);
+}
+
+/// Records commands that copy the pixels of `target`, which must have just been drawn to by a
+/// render pass built by `build_offscreen_render_pass`, into its readback buffer.
+pub fn record_readback<B: Backend>(
+    command_buffer: &mut CommandBuffer<B, Graphics, OneShot>,
+    target: &OffscreenTarget<B>,
+) {
+    command_buffer.pipeline_barrier(
+        PipelineStage::COLOR_ATTACHMENT_OUTPUT..PipelineStage::TRANSFER,
+        Dependencies::empty(),
+        &[Barrier::Image {
+            states: (Access::COLOR_ATTACHMENT_WRITE, Layout::TransferSrcOptimal)
+                ..(Access::TRANSFER_READ, Layout::TransferSrcOptimal),
+            target: &target.image,
+            range: SubresourceRange {
+                aspects: Aspects::COLOR,
+                levels: 0..1,
+                layers: 0..1,
+            },
+        }],
+    );
+
+    command_buffer.copy_image_to_buffer(
+        &target.image,
+        Layout::TransferSrcOptimal,
+        target.readback_buffer.buffer(),
+        &[BufferImageCopy {
+            buffer_offset: 0,
+            buffer_width: target.extent.width,
+            buffer_height: target.extent.height,
+            image_layers: SubresourceLayers {
+                aspects: Aspects::COLOR,
+                level: 0,
+                layers: 0..1,
+            },
+            image_offset: Offset { x: 0, y: 0, z: 0 },
+            image_extent: Extent {
+                width: target.extent.width,
+                height: target.extent.height,
+                depth: 1,
+            },
+        }],
+    );
+
+    command_buffer.pipeline_barrier(
+        PipelineStage::TRANSFER..PipelineStage::HOST,
+        Dependencies::empty(),
+        &[Barrier::Buffer {
+            states: BufferAccess::TRANSFER_WRITE..BufferAccess::HOST_READ,
+            target: target.readback_buffer.buffer(),
+        }],
+    );
+}
+
+/// Returns the pixels copied by the last `record_readback` as tightly packed RGBA8, top row
+/// first.
+///
+/// The commands recorded by `record_readback` must have finished executing.
+pub fn read_pixels<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    target: &OffscreenTarget<B>,
+) -> Vec<u8> {
+    read_host_buffer(
+        device,
+        &target.readback_buffer,
+        readback_size(target.extent),
+    )
+}
+
+fn readback_size(extent: Extent2D) -> u64 {
+    extent.width as u64 * extent.height as u64 * BYTES_PER_PIXEL as u64
+}
+
+/// Renders conrod primitives into an offscreen image and reads back the result, for use where
+/// there is no window or surface.
+///
+/// Each call to `render` waits for the GPU to finish before returning.
+pub struct OffscreenRenderer<B: Backend> {
+    device: Arc<<B as gfx_hal::Backend>::Device>,
+    memory_types: Vec<MemoryType>,
+    renderer: Renderer<B>,
+    target: OffscreenTarget<B>,
+    command_pool: CommandPool<B, Graphics>,
+    fence: <B as gfx_hal::Backend>::Fence,
+}
+
+impl<B: Backend> OffscreenRenderer<B> {
+    pub fn new(
+        device: Arc<<B as gfx_hal::Backend>::Device>,
+        physical_device: &<B as gfx_hal::Backend>::PhysicalDevice,
+        graphics_queue_group: &QueueGroup<B, Graphics>,
+        extent: Extent2D,
+        alpha_mode: AlphaMode,
+    ) -> Result<OffscreenRenderer<B>, Error> {
+        let memory_types = physical_device.memory_properties().memory_types;
+
+        let renderer = Renderer::new(
+            device.clone(),
+            physical_device,
+            graphics_queue_group,
+            build_offscreen_render_pass::<B>(&device, OFFSCREEN_FORMAT)?,
+            extent,
+            alpha_mode,
+            1,
+        )?;
+
+        let target =
+            match build_offscreen_target(&device, &memory_types, renderer.render_pass(), extent) {
+                Ok(target) => target,
+                Err(error) => {
+                    renderer.destroy();
+                    return Err(error);
+                }
+            };
+
+        let command_pool = device.create_command_pool_typed(
+            graphics_queue_group,
+            CommandPoolCreateFlags::TRANSIENT,
+            1,
+        );
+
+        let fence = device.create_fence(false);
+
+        Ok(OffscreenRenderer {
+            device,
+            memory_types,
+            renderer,
+            target,
+            command_pool,
+            fence,
+        })
+    }
+
+    /// The renderer that draws into the offscreen image, for loading textures and changing the
+    /// clear color.
+    pub fn renderer(&mut self) -> &mut Renderer<B> {
+        &mut self.renderer
+    }
+
+    pub fn extent(&self) -> Extent2D {
+        self.target.extent
+    }
+
+    /// Replaces the offscreen image with one of size `extent`. If that fails, the old image is
+    /// kept.
+    pub fn resize(&mut self, extent: Extent2D) -> Result<(), Error> {
+        if extent == self.target.extent {
+            return Ok(());
+        }
+
+        let target = build_offscreen_target(
+            &self.device,
+            &self.memory_types,
+            self.renderer.render_pass(),
+            extent,
+        )?;
+
+        destroy_offscreen_target(&self.device, ::std::mem::replace(&mut self.target, target));
+
+        self.renderer.resize(extent);
+
+        Ok(())
+    }
+
+    /// Draws `primitives` into the offscreen image and returns its pixels as tightly packed RGBA8,
+    /// top row first.
+    pub fn render(
+        &mut self,
+        queue: &mut CommandQueue<B, Graphics>,
+        primitives: Primitives,
+        image_map: &image::Map<Texture<B>>,
+        dpi_factor: f64,
+    ) -> Result<Vec<u8>, Error> {
+        self.renderer.fill(primitives, image_map, dpi_factor)?;
+
+        let submit = {
+            let mut command_buffer = self.command_pool.acquire_command_buffer::<OneShot>(false);
+
+            self.renderer
+                .draw(&mut command_buffer, &self.target.framebuffer, image_map)?;
+
+            record_readback(&mut command_buffer, &self.target);
+
+            command_buffer.finish()
+        };
+
+        queue.submit(Submission::new().submit(Some(submit)), Some(&self.fence));
+
+        self.device.wait_for_fence(&self.fence, !0);
+        self.device.reset_fence(&self.fence);
+        self.command_pool.reset();
+
+        Ok(read_pixels(&self.device, &self.target))
+    }
+
+    /// Destroys the offscreen renderer. The GPU must not be using any of it.
+    pub fn destroy(self) {
+        self.device.destroy_fence(self.fence);
+        self.device
+            .destroy_command_pool(self.command_pool.into_raw());
+        destroy_offscreen_target(&self.device, self.target);
+        self.renderer.destroy();
+    }
+}
@@ -0,0 +1,148 @@
+use conrod::event::Input;
+use conrod::input::{Button, Motion, MouseButton};
+use conrod::{Point, Ui};
+
+/// An input to be handled by the `Ui` before the update for `frame`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptedInput {
+    pub frame: u64,
+    pub input: Input,
+}
+
+/// Feeds scripted inputs into a `Ui` on a fixed frame schedule, in place of a window.
+///
+/// Each frame, call `step` and then update the widgets of the `Ui`.
+#[derive(Clone, Debug)]
+pub struct Playback {
+    inputs: Vec<ScriptedInput>,
+    next_input: usize,
+    frame: u64,
+}
+
+impl Playback {
+    pub fn new(mut inputs: Vec<ScriptedInput>) -> Playback {
+        // A stable sort keeps inputs for the same frame in the order they were given.
+        inputs.sort_by_key(|input| input.frame);
+
+        Playback {
+            inputs,
+            next_input: 0,
+            frame: 0,
+        }
+    }
+
+    /// The frame that the next `step` handles inputs for.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Whether every input has been handled.
+    pub fn is_finished(&self) -> bool {
+        self.next_input == self.inputs.len()
+    }
+
+    /// The frame after which every input has been handled.
+    pub fn last_frame(&self) -> u64 {
+        self.inputs.last().map(|input| input.frame).unwrap_or(0)
+    }
+
+    /// Passes the inputs for the current frame to `ui` and advances to the next frame.
+    pub fn step(&mut self, ui: &mut Ui) {
+        while let Some(scripted_input) = self.inputs.get(self.next_input) {
+            if scripted_input.frame > self.frame {
+                break;
+            }
+
+            ui.handle_event(scripted_input.input.clone());
+            self.next_input += 1;
+        }
+
+        self.frame += 1;
+    }
+}
+
+/// Builds the inputs for a `Playback`.
+///
+/// Each input is scheduled one frame after the previous one, so that widgets see every change.
+/// Points are in the coordinates of the `Ui`, with the origin at the centre of the window and y
+/// pointing up.
+#[derive(Clone, Debug, Default)]
+pub struct Script {
+    inputs: Vec<ScriptedInput>,
+    frame: u64,
+}
+
+impl Script {
+    pub fn new() -> Script {
+        Script::default()
+    }
+
+    /// Schedules `input` for the current frame and moves on to the next frame.
+    pub fn input(mut self, input: Input) -> Script {
+        self.inputs.push(ScriptedInput {
+            frame: self.frame,
+            input,
+        });
+        self.frame += 1;
+        self
+    }
+
+    /// Leaves `frames` frames without input.
+    pub fn wait(mut self, frames: u64) -> Script {
+        self.frame += frames;
+        self
+    }
+
+    pub fn move_to(self, point: Point) -> Script {
+        self.input(Input::Motion(Motion::MouseCursor {
+            x: point[0],
+            y: point[1],
+        }))
+    }
+
+    pub fn press(self, button: MouseButton) -> Script {
+        self.input(Input::Press(Button::Mouse(button)))
+    }
+
+    pub fn release(self, button: MouseButton) -> Script {
+        self.input(Input::Release(Button::Mouse(button)))
+    }
+
+    /// Moves to `point` and clicks the left mouse button there.
+    pub fn click(self, point: Point) -> Script {
+        self.move_to(point)
+            .press(MouseButton::Left)
+            .release(MouseButton::Left)
+    }
+
+    /// Presses the left mouse button at `from`, moves to `to` over `steps` frames, and releases
+    /// it there.
+    pub fn drag(self, from: Point, to: Point, steps: u32) -> Script {
+        let steps = steps.max(1);
+
+        let mut script = self.move_to(from).press(MouseButton::Left);
+
+        for step in 1..=steps {
+            let t = step as f64 / steps as f64;
+            script = script.move_to([
+                from[0] + (to[0] - from[0]) * t,
+                from[1] + (to[1] - from[1]) * t,
+            ]);
+        }
+
+        script.release(MouseButton::Left)
+    }
+
+    /// Scrolls by `x` and `y` at the current mouse position.
+    pub fn scroll(self, x: f64, y: f64) -> Script {
+        self.input(Input::Motion(Motion::Scroll { x, y }))
+    }
+
+    pub fn text(self, text: &str) -> Script {
+        self.input(Input::Text(text.to_string()))
+    }
+
+    pub fn build(self) -> Vec<ScriptedInput> {
+        self.inputs
+    }
+}
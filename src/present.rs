@@ -0,0 +1,104 @@
+use gfx_hal::window::PresentMode;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// How frames are synchronized with the display, which decides the `PresentMode` of a swapchain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresentPolicy {
+    /// Never tear, and present the latest frame at each vertical blank.
+    VsyncOn,
+    /// Present frames as soon as they are ready, even if that tears.
+    VsyncOff,
+    /// Present the latest frame as soon as possible, preferring not to tear.
+    LowLatency,
+    /// Wait for each vertical blank, so that no more frames are rendered than are displayed.
+    PowerSaving,
+}
+
+impl Default for PresentPolicy {
+    fn default() -> PresentPolicy {
+        PresentPolicy::VsyncOn
+    }
+}
+
+impl PresentPolicy {
+    /// Every policy, in the order that `next` cycles through them.
+    pub const ALL: [PresentPolicy; 4] = [
+        PresentPolicy::VsyncOn,
+        PresentPolicy::VsyncOff,
+        PresentPolicy::LowLatency,
+        PresentPolicy::PowerSaving,
+    ];
+
+    /// The present modes that fit the policy, best first. Each ends with `Fifo`, which every
+    /// surface supports.
+    pub fn preferred_modes(self) -> &'static [PresentMode] {
+        match self {
+            PresentPolicy::VsyncOn => &[PresentMode::Mailbox, PresentMode::Fifo],
+            PresentPolicy::VsyncOff => &[
+                PresentMode::Immediate,
+                PresentMode::Mailbox,
+                PresentMode::Relaxed,
+                PresentMode::Fifo,
+            ],
+            PresentPolicy::LowLatency => &[
+                PresentMode::Mailbox,
+                PresentMode::Immediate,
+                PresentMode::Relaxed,
+                PresentMode::Fifo,
+            ],
+            PresentPolicy::PowerSaving => &[PresentMode::Fifo],
+        }
+    }
+
+    /// The first of the preferred modes that is available, falling back to `Fifo`.
+    pub fn choose(self, available: &[PresentMode]) -> PresentMode {
+        self.preferred_modes()
+            .iter()
+            .cloned()
+            .find(|mode| available.contains(mode))
+            .unwrap_or(PresentMode::Fifo)
+    }
+
+    /// The policy after this one, wrapping around.
+    pub fn next(self) -> PresentPolicy {
+        let index = PresentPolicy::ALL
+            .iter()
+            .position(|&policy| policy == self)
+            .unwrap();
+
+        PresentPolicy::ALL[(index + 1) % PresentPolicy::ALL.len()]
+    }
+}
+
+/// Parses `vsync`, `no-vsync`, `low-latency` or `power-saving`.
+impl FromStr for PresentPolicy {
+    type Err = ParsePresentPolicyError;
+
+    fn from_str(policy: &str) -> Result<PresentPolicy, ParsePresentPolicyError> {
+        match policy {
+            "vsync" => Ok(PresentPolicy::VsyncOn),
+            "no-vsync" => Ok(PresentPolicy::VsyncOff),
+            "low-latency" => Ok(PresentPolicy::LowLatency),
+            "power-saving" => Ok(PresentPolicy::PowerSaving),
+            _ => Err(ParsePresentPolicyError(policy.to_string())),
+        }
+    }
+}
+
+/// A string that is not a valid `PresentPolicy`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsePresentPolicyError(pub String);
+
+impl fmt::Display for ParsePresentPolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid present policy {:?}, expected vsync, no-vsync, low-latency or power-saving",
+            self.0
+        )
+    }
+}
+
+impl Error for ParsePresentPolicyError {}
@@ -0,0 +1,255 @@
+use conrod::event::Input;
+use conrod::input::{Button, Key, Motion, MouseButton};
+use playback::ScriptedInput;
+use serde_json;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::io::{BufRead, Write};
+use std::time::{Duration, Instant};
+
+/// An input along with the time at which it was received, relative to the start of a recording.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedInput {
+    pub time: Duration,
+    pub input: Input,
+}
+
+/// Writes inputs to a recording as they are received.
+///
+/// A recording has one JSON object per line, such as
+/// `{"time":1.5,"type":"press","button":{"mouse":"left"}}`, where `time` is in seconds. Each line
+/// is flushed as it is written, so that a recording survives a crash. Touch and controller inputs
+/// are not recorded.
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Instant,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Starts a recording that is written to `writer`.
+    pub fn new(writer: W) -> Recorder<W> {
+        Recorder {
+            writer,
+            start: Instant::now(),
+        }
+    }
+
+    /// Writes `input` to the recording, timestamped with the time since the recording started.
+    pub fn record(&mut self, input: &Input) -> io::Result<()> {
+        let elapsed = self.start.elapsed();
+
+        let recorded_input = match RecordedInput::from_input(input) {
+            Some(recorded_input) => recorded_input,
+            None => return Ok(()),
+        };
+
+        let event = RecordedEvent {
+            time: elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9,
+            input: recorded_input,
+        };
+
+        serde_json::to_writer(&mut self.writer, &event)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads every input from a recording written by a `Recorder`. Blank lines are ignored.
+pub fn read_recording<R: BufRead>(reader: R) -> Result<Vec<TimedInput>, RecordingError> {
+    let mut inputs = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let event: RecordedEvent =
+            serde_json::from_str(&line).map_err(|error| RecordingError::Parse {
+                line: index + 1,
+                error,
+            })?;
+
+        inputs.push(TimedInput {
+            time: Duration::new(event.time.trunc() as u64, (event.time.fract() * 1e9) as u32),
+            input: event.input.into_input(),
+        });
+    }
+
+    Ok(inputs)
+}
+
+/// Schedules `inputs` for the frames in which they were received, assuming frames of
+/// `frame_duration`, for playback with a `Playback`.
+pub fn to_scripted_inputs(inputs: &[TimedInput], frame_duration: Duration) -> Vec<ScriptedInput> {
+    let nanos =
+        |duration: Duration| duration.as_secs() * 1_000_000_000 + duration.subsec_nanos() as u64;
+    let frame_nanos = nanos(frame_duration).max(1);
+
+    inputs
+        .iter()
+        .map(|timed_input| ScriptedInput {
+            frame: nanos(timed_input.time) / frame_nanos,
+            input: timed_input.input.clone(),
+        })
+        .collect()
+}
+
+/// An error reading a recording.
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(io::Error),
+    /// The line with the given number, counting from 1, is not a valid input.
+    Parse {
+        line: usize,
+        error: serde_json::Error,
+    },
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordingError::Io(error) => write!(f, "Failed to read recording: {}", error),
+            RecordingError::Parse { line, error } => {
+                write!(f, "Invalid input on line {} of recording: {}", line, error)
+            }
+        }
+    }
+}
+
+impl Error for RecordingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RecordingError::Io(error) => Some(error),
+            RecordingError::Parse { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for RecordingError {
+    fn from(error: io::Error) -> RecordingError {
+        RecordingError::Io(error)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedEvent {
+    time: f64,
+    #[serde(flatten)]
+    input: RecordedInput,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RecordedInput {
+    Press { button: RecordedButton },
+    Release { button: RecordedButton },
+    MouseCursor { x: f64, y: f64 },
+    MouseRelative { x: f64, y: f64 },
+    Scroll { x: f64, y: f64 },
+    Text { text: String },
+    Resize { width: f64, height: f64 },
+    Focus { focused: bool },
+    Redraw,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RecordedButton {
+    /// The value of a `Key`.
+    Keyboard(u32),
+    Mouse(RecordedMouseButton),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RecordedMouseButton {
+    Unknown,
+    Left,
+    Right,
+    Middle,
+    X1,
+    X2,
+    Button6,
+    Button7,
+    Button8,
+}
+
+impl RecordedInput {
+    fn from_input(input: &Input) -> Option<RecordedInput> {
+        Some(match *input {
+            Input::Press(button) => RecordedInput::Press {
+                button: RecordedButton::from_button(button)?,
+            },
+            Input::Release(button) => RecordedInput::Release {
+                button: RecordedButton::from_button(button)?,
+            },
+            Input::Motion(Motion::MouseCursor { x, y }) => RecordedInput::MouseCursor { x, y },
+            Input::Motion(Motion::MouseRelative { x, y }) => RecordedInput::MouseRelative { x, y },
+            Input::Motion(Motion::Scroll { x, y }) => RecordedInput::Scroll { x, y },
+            Input::Motion(Motion::ControllerAxis(_)) => return None,
+            Input::Touch(_) => return None,
+            Input::Text(ref text) => RecordedInput::Text { text: text.clone() },
+            Input::Resize(width, height) => RecordedInput::Resize { width, height },
+            Input::Focus(focused) => RecordedInput::Focus { focused },
+            Input::Redraw => RecordedInput::Redraw,
+        })
+    }
+
+    fn into_input(self) -> Input {
+        match self {
+            RecordedInput::Press { button } => Input::Press(button.into_button()),
+            RecordedInput::Release { button } => Input::Release(button.into_button()),
+            RecordedInput::MouseCursor { x, y } => Input::Motion(Motion::MouseCursor { x, y }),
+            RecordedInput::MouseRelative { x, y } => Input::Motion(Motion::MouseRelative { x, y }),
+            RecordedInput::Scroll { x, y } => Input::Motion(Motion::Scroll { x, y }),
+            RecordedInput::Text { text } => Input::Text(text),
+            RecordedInput::Resize { width, height } => Input::Resize(width, height),
+            RecordedInput::Focus { focused } => Input::Focus(focused),
+            RecordedInput::Redraw => Input::Redraw,
+        }
+    }
+}
+
+impl RecordedButton {
+    fn from_button(button: Button) -> Option<RecordedButton> {
+        Some(match button {
+            Button::Keyboard(key) => RecordedButton::Keyboard(key as u32),
+            Button::Mouse(mouse_button) => RecordedButton::Mouse(match mouse_button {
+                MouseButton::Unknown => RecordedMouseButton::Unknown,
+                MouseButton::Left => RecordedMouseButton::Left,
+                MouseButton::Right => RecordedMouseButton::Right,
+                MouseButton::Middle => RecordedMouseButton::Middle,
+                MouseButton::X1 => RecordedMouseButton::X1,
+                MouseButton::X2 => RecordedMouseButton::X2,
+                MouseButton::Button6 => RecordedMouseButton::Button6,
+                MouseButton::Button7 => RecordedMouseButton::Button7,
+                MouseButton::Button8 => RecordedMouseButton::Button8,
+            }),
+            Button::Controller(_) => return None,
+        })
+    }
+
+    fn into_button(self) -> Button {
+        match self {
+            RecordedButton::Keyboard(key) => Button::Keyboard(Key::from(key)),
+            RecordedButton::Mouse(mouse_button) => Button::Mouse(match mouse_button {
+                RecordedMouseButton::Unknown => MouseButton::Unknown,
+                RecordedMouseButton::Left => MouseButton::Left,
+                RecordedMouseButton::Right => MouseButton::Right,
+                RecordedMouseButton::Middle => MouseButton::Middle,
+                RecordedMouseButton::X1 => MouseButton::X1,
+                RecordedMouseButton::X2 => MouseButton::X2,
+                RecordedMouseButton::Button6 => MouseButton::Button6,
+                RecordedMouseButton::Button7 => MouseButton::Button7,
+                RecordedMouseButton::Button8 => MouseButton::Button8,
+            }),
+        }
+    }
+}
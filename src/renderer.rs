@@ -1,37 +1,1453 @@
+use buffer::{build_host_buffer, destroy_host_buffer, write_host_buffer, HostBuffer};
+use conrod::color::{Color, Rgba};
+use conrod::image;
 use conrod::render::Primitive;
 use conrod::render::PrimitiveKind;
-use gfx_hal::command::CommandBuffer;
-use gfx_hal::command::OneShot;
-use gfx_hal::pool::CommandPool;
+use conrod::render::Primitives;
+use conrod::{Point, Rect};
+use error::Error;
+use gfx_hal::buffer::Usage as BufferUsage;
+use gfx_hal::command::{ClearColor, ClearValue, CommandBuffer, OneShot};
+use gfx_hal::device::Device;
+use gfx_hal::format::{Aspects, Format};
+use gfx_hal::image::Layout;
+use gfx_hal::pass::{
+    Attachment, AttachmentLoadOp, AttachmentOps, AttachmentStoreOp, SubpassDependency, SubpassDesc,
+};
+use gfx_hal::pool::{CommandPool, CommandPoolCreateFlags};
+use gfx_hal::pso::{
+    AttributeDesc, BakedStates, BlendState, ColorBlendDesc, ColorMask, Constant,
+    DescriptorSetLayoutBinding, DescriptorType, Element, EntryPoint, GraphicsPipelineDesc,
+    GraphicsShaderSet, Rasterizer, Rect as PsoRect, ShaderStageFlags, Specialization, Subpass,
+    VertexBufferDesc, Viewport,
+};
 use gfx_hal::queue::capability::Graphics;
+use gfx_hal::queue::{CommandQueue, QueueGroup};
+use gfx_hal::window::Extent2D;
 use gfx_hal::Backend;
+use gfx_hal::MemoryType;
+use gfx_hal::PhysicalDevice;
+use gfx_hal::Primitive as PrimitiveTopology;
+use glyph_cache::GlyphAtlas;
+use std::mem::{replace, size_of};
+use std::ops::Range;
+use std::path::Path;
+use std::slice;
+use std::sync::Arc;
+use texture;
+use texture::{
+    build_texture, destroy_texture, record_texture_clear, record_texture_upload,
+    stage_texture_regions, Texture, TextureRegion,
+};
 
 static VERTEX_SHADER: &[u8] = include_glsl_vs!("src/conrod.vert");
 static FRAGMENT_SHADER: &[u8] = include_glsl_fs!("src/conrod.frag");
 
-pub fn render<B: Backend>(graphics_command_pool: &mut CommandPool<B, Graphics>) {
-    let mut graphics_command_buffer =
-        graphics_command_pool.acquire_command_buffer::<OneShot>(false);
+/// The vertex is coloured by sampling the glyph cache as an alpha mask.
+pub const MODE_TEXT: u32 = 0;
+/// The vertex is coloured by sampling an image texture.
+pub const MODE_IMAGE: u32 = 1;
+/// The vertex is coloured by its own color only.
+pub const MODE_GEOMETRY: u32 = 2;
+
+/// Implemented by the values stored in the `image::Map` passed to `render_primitives`.
+pub trait ImageDimensions {
+    /// The width and height of the image in pixels.
+    fn dimensions(&self) -> (u32, u32);
 }
 
-pub fn render_primitive<B: Backend>(
-    command_buffer: &mut CommandBuffer<B, Graphics>,
-    primitive: &Primitive,
-) {
-    match &primitive.kind {
-        PrimitiveKind::Rectangle { color } => (),
-        PrimitiveKind::TrianglesSingleColor { triangles, color } => (),
-        PrimitiveKind::TrianglesMultiColor { triangles } => (),
-        PrimitiveKind::Image {
+/// A single vertex as consumed by the conrod shaders.
+///
+/// Positions are in the space described by the `Transform` in use, colors are linear RGBA.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct Vertex {
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+    pub mode: u32,
+}
+
+impl Vertex {
+    /// The layout of a vertex buffer containing `Vertex`es bound at `binding`.
+    pub fn buffer_desc(binding: u32) -> VertexBufferDesc {
+        VertexBufferDesc {
+            binding,
+            stride: size_of::<Vertex>() as u32,
+            rate: 0,
+        }
+    }
+
+    /// The vertex attributes, in shader location order, read from the vertex buffer bound at
+    /// `binding`.
+    pub fn attribute_descs(binding: u32) -> Vec<AttributeDesc> {
+        let formats = [
+            (Format::Rg32Float, size_of::<[f32; 2]>()),
+            (Format::Rg32Float, size_of::<[f32; 2]>()),
+            (Format::Rgba32Float, size_of::<[f32; 4]>()),
+            (Format::R32Uint, size_of::<u32>()),
+        ];
+
+        let mut offset = 0;
+
+        formats
+            .iter()
+            .enumerate()
+            .map(|(location, &(format, size))| {
+                let desc = AttributeDesc {
+                    location: location as u32,
+                    binding,
+                    element: Element {
+                        format,
+                        offset: offset as u32,
+                    },
+                };
+                offset += size;
+                desc
+            })
+            .collect()
+    }
+}
+
+/// The transform applied by the vertex shader to map vertex positions into normalized device
+/// coordinates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct Transform {
+    pub scale: [f32; 2],
+    pub translate: [f32; 2],
+}
+
+impl Transform {
+    /// For positions that are already in normalized device coordinates.
+    pub fn ndc() -> Transform {
+        Transform {
+            scale: [1.0, 1.0],
+            translate: [0.0, 0.0],
+        }
+    }
+
+    /// For positions in framebuffer pixels, with the origin at the top left.
+    pub fn pixels(width: u32, height: u32) -> Transform {
+        Transform {
+            scale: [2.0 / width as f32, 2.0 / height as f32],
+            translate: [-1.0, -1.0],
+        }
+    }
+
+    /// The transform in the form expected by `push_graphics_constants`.
+    pub fn push_constants(&self) -> [u32; 4] {
+        [
+            self.scale[0].to_bits(),
+            self.scale[1].to_bits(),
+            self.translate[0].to_bits(),
+            self.translate[1].to_bits(),
+        ]
+    }
+}
+
+/// A contiguous range of vertices that are drawn together in a single draw call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawCommand {
+    /// The image sampled by the `MODE_IMAGE` vertices in the range, if there are any.
+    pub image_id: Option<image::Id>,
+    /// The rectangle outside of which nothing is drawn, in the same coordinates as the conrod
+    /// primitives.
+    pub scissor: Rect,
+    pub vertices: Range<u32>,
+}
+
+/// The vertices for a frame, and the commands that draw them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DrawList {
+    pub vertices: Vec<Vertex>,
+    pub commands: Vec<DrawCommand>,
+}
+
+/// The amount of work needed to draw a `DrawList`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DrawStats {
+    /// The number of draw calls.
+    pub batches: usize,
+    pub vertices: usize,
+}
+
+impl DrawList {
+    pub fn new() -> DrawList {
+        DrawList::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.commands.clear();
+    }
+
+    pub fn stats(&self) -> DrawStats {
+        DrawStats {
+            batches: self.commands.len(),
+            vertices: self.vertices.len(),
+        }
+    }
+
+    /// Adds the vertices from `start` to the end of the list to a draw command.
+    ///
+    /// The last command is extended rather than starting a new one unless the scissor changes or
+    /// both sample different images. Vertices that don't sample an image can share a command with
+    /// any image.
+    fn record(&mut self, start: usize, image_id: Option<image::Id>, scissor: Rect) {
+        let end = self.vertices.len() as u32;
+        let start = start as u32;
+
+        if start == end {
+            return;
+        }
+
+        match self.commands.last_mut() {
+            Some(command)
+                if command.scissor == scissor
+                    && command.vertices.end == start
+                    && (command.image_id == image_id
+                        || command.image_id.is_none()
+                        || image_id.is_none()) =>
+            {
+                command.image_id = command.image_id.or(image_id);
+                command.vertices.end = end;
+                return;
+            }
+            _ => (),
+        }
+
+        self.commands.push(DrawCommand {
             image_id,
-            color,
-            source_rect,
-        } => (),
-        PrimitiveKind::Text {
-            color,
-            text,
-            font_id,
-        } => (),
-        PrimitiveKind::Other(_) => (),
+            scissor,
+            vertices: start..end,
+        });
+    }
+}
+
+/// Converts `scissor`, in the coordinates of a conrod `Ui` of size `window_size`, into a rectangle
+/// of framebuffer pixels clamped to `extent`.
+pub fn scissor_to_framebuffer(scissor: &Rect, window_size: [f64; 2], extent: Extent2D) -> PsoRect {
+    let scale = [
+        extent.width as f64 / window_size[0],
+        extent.height as f64 / window_size[1],
+    ];
+
+    let (left, right, bottom, top) = scissor.l_r_b_t();
+
+    // Conrod's origin is at the centre of the window with y pointing up, whereas the framebuffer's
+    // is at the top left with y pointing down.
+    let clamp = |value: f64, max: u32| value.round().max(0.0).min(max as f64) as i16;
+
+    let x0 = clamp((left + window_size[0] / 2.0) * scale[0], extent.width);
+    let x1 = clamp((right + window_size[0] / 2.0) * scale[0], extent.width);
+    let y0 = clamp((window_size[1] / 2.0 - top) * scale[1], extent.height);
+    let y1 = clamp((window_size[1] / 2.0 - bottom) * scale[1], extent.height);
+
+    PsoRect {
+        x: x0,
+        y: y0,
+        w: x1 - x0,
+        h: y1 - y0,
+    }
+}
+
+/// How the colors produced by the conrod shaders are blended into the framebuffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AlphaMode {
+    /// Colors are blended using straight (non-premultiplied) alpha.
+    Straight,
+    /// Colors are premultiplied by alpha in the fragment shader and blended accordingly.
+    Premultiplied,
+}
+
+/// The graphics pipeline used to draw conrod primitives, along with the layouts it was built from.
+pub struct Pipeline<B: Backend> {
+    /// Layout of descriptor set 0, which holds the glyph cache texture.
+    pub glyph_cache_set_layout: <B as gfx_hal::Backend>::DescriptorSetLayout,
+    /// Layout of descriptor set 1, which holds the texture of the image being drawn.
+    pub image_set_layout: <B as gfx_hal::Backend>::DescriptorSetLayout,
+    pub layout: <B as gfx_hal::Backend>::PipelineLayout,
+    pub pipeline: <B as gfx_hal::Backend>::GraphicsPipeline,
+}
+
+/// Builds the pipeline for drawing conrod primitives in the first subpass of `render_pass`.
+///
+/// The pipeline must be rebuilt whenever `render_pass` is, for example because the surface format
+/// changed. Viewport and scissor are dynamic state.
+pub fn build_pipeline<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    render_pass: &<B as gfx_hal::Backend>::RenderPass,
+    alpha_mode: AlphaMode,
+) -> Result<Pipeline<B>, Error> {
+    let sampler_binding = DescriptorSetLayoutBinding {
+        binding: 0,
+        ty: DescriptorType::CombinedImageSampler,
+        count: 1,
+        stage_flags: ShaderStageFlags::FRAGMENT,
+        immutable_samplers: false,
+    };
+
+    let glyph_cache_set_layout =
+        device.create_descriptor_set_layout(&[sampler_binding.clone()], &[]);
+    let image_set_layout = device.create_descriptor_set_layout(&[sampler_binding], &[]);
+
+    let transform_size = (size_of::<Transform>() / size_of::<u32>()) as u32;
+
+    let layout = device.create_pipeline_layout(
+        vec![&glyph_cache_set_layout, &image_set_layout],
+        &[(ShaderStageFlags::VERTEX, 0..transform_size)],
+    );
+
+    let vertex_module = device.create_shader_module(VERTEX_SHADER)?;
+    let fragment_module = device.create_shader_module(FRAGMENT_SHADER)?;
+
+    let pipeline = {
+        let fragment_specialization = [Specialization {
+            id: 0,
+            value: Constant::Bool(alpha_mode == AlphaMode::Premultiplied),
+        }];
+
+        let shaders = GraphicsShaderSet {
+            vertex: EntryPoint::<B> {
+                entry: "main",
+                module: &vertex_module,
+                specialization: &[],
+            },
+            hull: None,
+            domain: None,
+            geometry: None,
+            fragment: Some(EntryPoint::<B> {
+                entry: "main",
+                module: &fragment_module,
+                specialization: &fragment_specialization,
+            }),
+        };
+
+        let subpass = Subpass {
+            index: 0,
+            main_pass: render_pass,
+        };
+
+        let mut desc = GraphicsPipelineDesc::new(
+            shaders,
+            PrimitiveTopology::TriangleList,
+            Rasterizer::FILL,
+            &layout,
+            subpass,
+        );
+
+        desc.blender.targets.push(ColorBlendDesc(
+            ColorMask::ALL,
+            match alpha_mode {
+                AlphaMode::Straight => BlendState::ALPHA,
+                AlphaMode::Premultiplied => BlendState::PREMULTIPLIED_ALPHA,
+            },
+        ));
+
+        desc.vertex_buffers.push(Vertex::buffer_desc(0));
+        desc.attributes.extend(Vertex::attribute_descs(0));
+
+        desc.baked_states = BakedStates {
+            viewport: None,
+            scissor: None,
+            blend_color: None,
+            depth_bounds: None,
+        };
+
+        device.create_graphics_pipeline(&desc, None)
+    };
+
+    device.destroy_shader_module(vertex_module);
+    device.destroy_shader_module(fragment_module);
+
+    Ok(Pipeline {
+        glyph_cache_set_layout,
+        image_set_layout,
+        layout,
+        pipeline: pipeline?,
+    })
+}
+
+pub fn destroy_pipeline<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    pipeline: Pipeline<B>,
+) {
+    device.destroy_graphics_pipeline(pipeline.pipeline);
+    device.destroy_pipeline_layout(pipeline.layout);
+    device.destroy_descriptor_set_layout(pipeline.image_set_layout);
+    device.destroy_descriptor_set_layout(pipeline.glyph_cache_set_layout);
+}
+
+/// Builds a render pass with a single subpass that clears and then draws to a single color
+/// attachment, which is left ready to be presented.
+///
+/// Fails if `surface_format` isn't a color format.
+pub fn build_render_pass<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    surface_format: Format,
+) -> Result<<B as gfx_hal::Backend>::RenderPass, Error> {
+    build_color_render_pass::<B>(device, surface_format, Layout::Present)
+}
+
+/// Like `build_render_pass`, but leaves the color attachment ready to be copied from instead of
+/// presented.
+pub fn build_offscreen_render_pass<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    format: Format,
+) -> Result<<B as gfx_hal::Backend>::RenderPass, Error> {
+    build_color_render_pass::<B>(device, format, Layout::TransferSrcOptimal)
+}
+
+fn build_color_render_pass<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    format: Format,
+    final_layout: Layout,
+) -> Result<<B as gfx_hal::Backend>::RenderPass, Error> {
+    if !format.surface_desc().aspects.contains(Aspects::COLOR) {
+        return Err(Error::UnsupportedFormat(format));
+    }
+
+    Ok(device.create_render_pass(
+        vec![Attachment {
+            format: Some(format),
+            samples: 1,
+            ops: AttachmentOps {
+                load: AttachmentLoadOp::Clear,
+                store: AttachmentStoreOp::Store,
+            },
+            stencil_ops: AttachmentOps {
+                load: AttachmentLoadOp::DontCare,
+                store: AttachmentStoreOp::DontCare,
+            },
+            layouts: Layout::Undefined..final_layout,
+        }],
+        vec![SubpassDesc {
+            colors: &[(0, Layout::ColorAttachmentOptimal)],
+            depth_stencil: None,
+            inputs: &[],
+            resolves: &[],
+            preserves: &[],
+        }],
+        vec![] as Vec<SubpassDependency>,
+    ))
+}
+
+/// The side length of the square glyph cache texture, in pixels.
+pub const GLYPH_CACHE_SIZE: u32 = 1024;
+
+/// The size in bytes of each frame's vertex buffer before it first has to grow.
+const INITIAL_VERTEX_BUFFER_SIZE: u64 = 4096 * size_of::<Vertex>() as u64;
+
+/// The buffers written for a frame by `Renderer::fill` and `Renderer::draw`, and read by the
+/// command buffer recorded by `draw`.
+struct FrameBuffers<B: Backend> {
+    vertex_buffer: HostBuffer<B>,
+    /// Holds newly rasterized glyphs on their way into the glyph cache.
+    staging_buffer: HostBuffer<B>,
+}
+
+fn build_frame_buffers<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    memory_types: &[MemoryType],
+) -> Result<FrameBuffers<B>, Error> {
+    let vertex_buffer = build_host_buffer(
+        device,
+        memory_types,
+        BufferUsage::VERTEX,
+        INITIAL_VERTEX_BUFFER_SIZE,
+    )?;
+
+    match build_host_buffer(device, memory_types, BufferUsage::TRANSFER_SRC, 0) {
+        Ok(staging_buffer) => Ok(FrameBuffers {
+            vertex_buffer,
+            staging_buffer,
+        }),
+        Err(error) => {
+            destroy_host_buffer(device, vertex_buffer);
+            Err(error)
+        }
+    }
+}
+
+fn destroy_frame_buffers<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    frame: FrameBuffers<B>,
+) {
+    destroy_host_buffer(device, frame.vertex_buffer);
+    destroy_host_buffer(device, frame.staging_buffer);
+}
+
+/// Draws conrod primitives into framebuffers compatible with a render pass.
+///
+/// Call `fill` with the primitives for a frame, then `draw` to record the commands that draw
+/// them. The renderer cycles through `frames_in_flight` sets of buffers, advancing on each
+/// `draw`, so the command buffer recorded by a `draw` must have finished executing by the time
+/// `frames_in_flight` more `draw`s have been recorded.
+pub struct Renderer<B: Backend> {
+    device: Arc<<B as gfx_hal::Backend>::Device>,
+    memory_types: Vec<MemoryType>,
+    render_pass: <B as gfx_hal::Backend>::RenderPass,
+    alpha_mode: AlphaMode,
+    pipeline: Pipeline<B>,
+    upload_command_pool: CommandPool<B, Graphics>,
+    glyph_atlas: GlyphAtlas,
+    glyph_cache: Texture<B>,
+    /// Regions of the glyph cache rasterized since the last `draw`.
+    glyph_regions: Vec<TextureRegion>,
+    frames: Vec<FrameBuffers<B>>,
+    frame_index: usize,
+    draw_list: DrawList,
+    extent: Extent2D,
+    window_size: [f64; 2],
+    clear_color: [f32; 4],
+}
+
+impl<B: Backend> Renderer<B> {
+    /// Takes ownership of `render_pass` and builds everything needed to draw conrod primitives
+    /// into framebuffers of size `extent` that are compatible with it.
+    ///
+    /// If anything can't be built, everything built so far is destroyed, including `render_pass`.
+    pub fn new(
+        device: Arc<<B as gfx_hal::Backend>::Device>,
+        physical_device: &<B as gfx_hal::Backend>::PhysicalDevice,
+        graphics_queue_group: &QueueGroup<B, Graphics>,
+        render_pass: <B as gfx_hal::Backend>::RenderPass,
+        extent: Extent2D,
+        alpha_mode: AlphaMode,
+        frames_in_flight: usize,
+    ) -> Result<Renderer<B>, Error> {
+        let memory_types = physical_device.memory_properties().memory_types;
+
+        let pipeline = match build_pipeline(&device, &render_pass, alpha_mode) {
+            Ok(pipeline) => pipeline,
+            Err(error) => {
+                device.destroy_render_pass(render_pass);
+                return Err(error);
+            }
+        };
+
+        let glyph_cache = match build_texture(
+            &device,
+            &memory_types,
+            &pipeline.glyph_cache_set_layout,
+            Format::R8Unorm,
+            GLYPH_CACHE_SIZE,
+            GLYPH_CACHE_SIZE,
+        ) {
+            Ok(glyph_cache) => glyph_cache,
+            Err(error) => {
+                destroy_pipeline(&device, pipeline);
+                device.destroy_render_pass(render_pass);
+                return Err(error);
+            }
+        };
+
+        let mut frames = Vec::with_capacity(frames_in_flight.max(1));
+
+        for _ in 0..frames_in_flight.max(1) {
+            match build_frame_buffers(&device, &memory_types) {
+                Ok(frame) => frames.push(frame),
+                Err(error) => {
+                    for frame in frames {
+                        destroy_frame_buffers(&device, frame);
+                    }
+
+                    destroy_texture(&device, glyph_cache);
+                    destroy_pipeline(&device, pipeline);
+                    device.destroy_render_pass(render_pass);
+                    return Err(error);
+                }
+            }
+        }
+
+        let upload_command_pool = device.create_command_pool_typed(
+            graphics_queue_group,
+            CommandPoolCreateFlags::TRANSIENT,
+            1,
+        );
+
+        Ok(Renderer {
+            device,
+            memory_types,
+            render_pass,
+            alpha_mode,
+            pipeline,
+            upload_command_pool,
+            glyph_atlas: GlyphAtlas::new(GLYPH_CACHE_SIZE, GLYPH_CACHE_SIZE),
+            glyph_cache,
+            glyph_regions: Vec::new(),
+            frames,
+            frame_index: 0,
+            draw_list: DrawList::new(),
+            extent,
+            window_size: [extent.width as f64, extent.height as f64],
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+        })
+    }
+
+    pub fn render_pass(&self) -> &<B as gfx_hal::Backend>::RenderPass {
+        &self.render_pass
+    }
+
+    /// Replaces the render pass, for example because the surface format changed, and rebuilds the
+    /// pipeline to match. The GPU must not be using the old render pass.
+    ///
+    /// If the pipeline can't be rebuilt, `render_pass` is destroyed and the renderer keeps the old
+    /// render pass and pipeline.
+    pub fn set_render_pass(
+        &mut self,
+        render_pass: <B as gfx_hal::Backend>::RenderPass,
+    ) -> Result<(), Error> {
+        let pipeline = match build_pipeline(&self.device, &render_pass, self.alpha_mode) {
+            Ok(pipeline) => pipeline,
+            Err(error) => {
+                self.device.destroy_render_pass(render_pass);
+                return Err(error);
+            }
+        };
+
+        destroy_pipeline(&self.device, replace(&mut self.pipeline, pipeline));
+
+        let old_render_pass = replace(&mut self.render_pass, render_pass);
+        self.device.destroy_render_pass(old_render_pass);
+
+        Ok(())
+    }
+
+    /// Sets the size of the framebuffers that will be passed to `draw`.
+    pub fn resize(&mut self, extent: Extent2D) {
+        self.extent = extent;
+    }
+
+    /// Forgets every glyph in the glyph cache, for example because the DPI factor changed and
+    /// text will be rasterized at a different scale from now on.
+    pub fn clear_glyph_cache(&mut self) {
+        self.glyph_atlas = GlyphAtlas::new(GLYPH_CACHE_SIZE, GLYPH_CACHE_SIZE);
+        self.glyph_regions.clear();
+    }
+
+    /// Sets the color that framebuffers are cleared to before drawing.
+    pub fn set_clear_color(&mut self, color: Color) {
+        self.clear_color = linear_color(color.to_rgb());
+    }
+
+    /// The vertices and draw commands produced by the last `fill`.
+    pub fn draw_list(&self) -> &DrawList {
+        &self.draw_list
+    }
+
+    pub fn stats(&self) -> DrawStats {
+        self.draw_list.stats()
+    }
+
+    /// Builds the vertices for `primitives` and writes them into the buffers for the next `draw`.
+    ///
+    /// `dpi_factor` is the number of framebuffer pixels per unit of the conrod `Ui`.
+    pub fn fill(
+        &mut self,
+        primitives: Primitives,
+        image_map: &image::Map<Texture<B>>,
+        dpi_factor: f64,
+    ) -> Result<(), Error> {
+        self.window_size = [
+            self.extent.width as f64 / dpi_factor,
+            self.extent.height as f64 / dpi_factor,
+        ];
+
+        render_primitives(
+            &mut self.draw_list,
+            &mut self.glyph_atlas,
+            image_map,
+            primitives,
+            self.window_size,
+            dpi_factor,
+        );
+
+        self.glyph_regions
+            .extend(self.glyph_atlas.take_dirty_regions());
+
+        write_host_buffer(
+            &self.device,
+            &self.memory_types,
+            &mut self.frames[self.frame_index].vertex_buffer,
+            vertices_as_bytes(&self.draw_list.vertices),
+        )
+    }
+
+    /// Records commands that update the glyph cache and then draw the primitives from the last
+    /// `fill` in a render pass on `framebuffer`.
+    pub fn draw(
+        &mut self,
+        command_buffer: &mut CommandBuffer<B, Graphics, OneShot>,
+        framebuffer: &<B as gfx_hal::Backend>::Framebuffer,
+        image_map: &image::Map<Texture<B>>,
+    ) -> Result<(), Error> {
+        // The glyph cache is bound for every draw, even before any glyphs are uploaded.
+        record_texture_clear(command_buffer, &mut self.glyph_cache);
+
+        if !self.glyph_regions.is_empty() {
+            let staging_buffer = &mut self.frames[self.frame_index].staging_buffer;
+
+            let offsets = stage_texture_regions(
+                &self.device,
+                &self.memory_types,
+                &self.glyph_cache,
+                staging_buffer,
+                &self.glyph_regions,
+            )?;
+
+            record_texture_upload(
+                command_buffer,
+                staging_buffer.buffer(),
+                &mut self.glyph_cache,
+                &self.glyph_regions,
+                &offsets,
+            );
+
+            self.glyph_regions.clear();
+        }
+
+        self.record_draw(command_buffer, framebuffer, image_map);
+
+        self.frame_index = (self.frame_index + 1) % self.frames.len();
+
+        Ok(())
+    }
+
+    /// Records a render pass that clears `framebuffer` to the clear color and executes the draw
+    /// commands from the last `fill`, reading vertices from the current frame's vertex buffer.
+    fn record_draw(
+        &self,
+        command_buffer: &mut CommandBuffer<B, Graphics, OneShot>,
+        framebuffer: &<B as gfx_hal::Backend>::Framebuffer,
+        image_map: &image::Map<Texture<B>>,
+    ) {
+        let extent = self.extent;
+        let vertex_count = self.draw_list.vertices.len() as u32;
+
+        let viewport = Viewport {
+            rect: PsoRect {
+                x: 0,
+                y: 0,
+                w: extent.width as i16,
+                h: extent.height as i16,
+            },
+            depth: 0.0..1.0,
+        };
+
+        command_buffer.set_viewports(0, &[viewport.clone()]);
+
+        {
+            let mut encoder = command_buffer.begin_render_pass_inline(
+                &self.render_pass,
+                framebuffer,
+                viewport.rect,
+                &[ClearValue::Color(ClearColor::Float(self.clear_color))],
+            );
+
+            encoder.bind_graphics_pipeline(&self.pipeline.pipeline);
+            encoder.bind_vertex_buffers(
+                0,
+                Some((self.frames[self.frame_index].vertex_buffer.buffer(), 0)),
+            );
+
+            // Set 1 must hold a valid texture even for draws that never sample it. The glyph
+            // cache's set is compatible because both set layouts are defined identically.
+            encoder.bind_graphics_descriptor_sets(
+                &self.pipeline.layout,
+                0,
+                vec![
+                    &self.glyph_cache.descriptor_set,
+                    &self.glyph_cache.descriptor_set,
+                ],
+                &[],
+            );
+
+            encoder.push_graphics_constants(
+                &self.pipeline.layout,
+                ShaderStageFlags::VERTEX,
+                0,
+                &Transform::ndc().push_constants(),
+            );
+
+            let mut bound_image_id = None;
+            let mut current_scissor = None;
+
+            for command in &self.draw_list.commands {
+                let vertices = command.vertices.start.min(vertex_count)
+                    ..command.vertices.end.min(vertex_count);
+
+                if vertices.start == vertices.end {
+                    continue;
+                }
+
+                let scissor = scissor_to_framebuffer(&command.scissor, self.window_size, extent);
+
+                if scissor.w == 0 || scissor.h == 0 {
+                    continue;
+                }
+
+                if current_scissor != Some(scissor) {
+                    encoder.set_scissors(0, &[scissor]);
+                    current_scissor = Some(scissor);
+                }
+
+                if command.image_id.is_some() && command.image_id != bound_image_id {
+                    if let Some(texture) = command
+                        .image_id
+                        .and_then(|image_id| image_map.get(&image_id))
+                    {
+                        encoder.bind_graphics_descriptor_sets(
+                            &self.pipeline.layout,
+                            1,
+                            Some(&texture.descriptor_set),
+                            &[],
+                        );
+                        bound_image_id = command.image_id;
+                    }
+                }
+
+                encoder.draw(vertices, 0..1);
+            }
+        }
+    }
+
+    /// Decodes a PNG or JPEG image into a texture that can be inserted into the `image::Map`
+    /// passed to `fill` and `draw`.
+    pub fn load_texture(
+        &mut self,
+        queue: &mut CommandQueue<B, Graphics>,
+        bytes: &[u8],
+    ) -> Result<Texture<B>, Error> {
+        texture::load_texture(
+            &self.device,
+            &self.memory_types,
+            queue,
+            &mut self.upload_command_pool,
+            &self.pipeline.image_set_layout,
+            bytes,
+        )
+    }
+
+    /// Like `load_texture`, but reads the image from the file at `path`.
+    pub fn load_texture_from_path<P: AsRef<Path>>(
+        &mut self,
+        queue: &mut CommandQueue<B, Graphics>,
+        path: P,
+    ) -> Result<Texture<B>, Error> {
+        texture::load_texture_from_path(
+            &self.device,
+            &self.memory_types,
+            queue,
+            &mut self.upload_command_pool,
+            &self.pipeline.image_set_layout,
+            path,
+        )
+    }
+
+    /// Destroys a texture created by `load_texture`. The GPU must not be using it.
+    pub fn destroy_texture(&self, texture: Texture<B>) {
+        destroy_texture(&self.device, texture);
+    }
+
+    /// Destroys the renderer, including its render pass. The GPU must not be using any of it.
+    pub fn destroy(self) {
+        for frame in self.frames {
+            destroy_frame_buffers(&self.device, frame);
+        }
+
+        destroy_texture(&self.device, self.glyph_cache);
+
+        self.device
+            .destroy_command_pool(self.upload_command_pool.into_raw());
+
+        destroy_pipeline(&self.device, self.pipeline);
+
+        self.device.destroy_render_pass(self.render_pass);
+    }
+}
+
+fn vertices_as_bytes(vertices: &[Vertex]) -> &[u8] {
+    unsafe {
+        slice::from_raw_parts(
+            vertices.as_ptr() as *const u8,
+            vertices.len() * size_of::<Vertex>(),
+        )
+    }
+}
+
+/// Replaces the contents of `draw_list` with the vertices and draw commands for every primitive
+/// in `primitives`.
+pub fn render_primitives<T: ImageDimensions>(
+    draw_list: &mut DrawList,
+    glyph_atlas: &mut GlyphAtlas,
+    image_map: &image::Map<T>,
+    primitives: Primitives,
+    window_size: [f64; 2],
+    dpi_factor: f64,
+) {
+    draw_list.clear();
+
+    let primitives = primitives.owned();
+
+    // The glyphs of every text primitive are cached at once, before any are drawn, so that
+    // caching the glyphs of one primitive can't evict those already drawn for another.
+    let mut walk = primitives.walk();
+    while let Some(primitive) = walk.next() {
+        if let PrimitiveKind::Text { text, font_id, .. } = primitive.kind {
+            glyph_atlas.queue_text(text, font_id, dpi_factor as f32);
+        }
+    }
+
+    glyph_atlas.cache_queued();
+
+    let mut walk = primitives.walk();
+    while let Some(primitive) = walk.next() {
+        render_primitive(
+            draw_list,
+            glyph_atlas,
+            image_map,
+            primitive,
+            window_size,
+            dpi_factor,
+        );
+    }
+}
+
+/// Appends the vertices for `primitive` to `draw_list`. The glyphs of any text must already have
+/// been cached in `glyph_atlas`, and glyphs that are not are left out.
+///
+/// `window_size` is the size of the conrod `Ui` in the same units as the primitive's coordinates,
+/// and `dpi_factor` is the number of physical pixels per unit.
+pub fn render_primitive<T: ImageDimensions>(
+    draw_list: &mut DrawList,
+    glyph_atlas: &GlyphAtlas,
+    image_map: &image::Map<T>,
+    primitive: Primitive,
+    window_size: [f64; 2],
+    dpi_factor: f64,
+) {
+    let to_ndc = |[x, y]: Point| -> [f32; 2] {
+        [
+            (x * 2.0 / window_size[0]) as f32,
+            -(y * 2.0 / window_size[1]) as f32,
+        ]
+    };
+
+    let rect_to_ndc = |rect: &Rect| -> [[f32; 2]; 2] {
+        let (left, right, bottom, top) = rect.l_r_b_t();
+        [to_ndc([left, top]), to_ndc([right, bottom])]
+    };
+
+    let start = draw_list.vertices.len();
+    let mut image = None;
+
+    {
+        let vertices = &mut draw_list.vertices;
+
+        match primitive.kind {
+            PrimitiveKind::Rectangle { color } => {
+                push_quad(
+                    vertices,
+                    rect_to_ndc(&primitive.rect),
+                    [[0.0, 0.0], [1.0, 1.0]],
+                    linear_color(color.to_rgb()),
+                    MODE_GEOMETRY,
+                );
+            }
+            PrimitiveKind::TrianglesSingleColor { triangles, color } => {
+                let color = linear_color(color);
+
+                for triangle in triangles.iter() {
+                    for point in triangle.0.iter() {
+                        vertices.push(Vertex {
+                            position: to_ndc(*point),
+                            uv: [0.0, 0.0],
+                            color,
+                            mode: MODE_GEOMETRY,
+                        });
+                    }
+                }
+            }
+            PrimitiveKind::TrianglesMultiColor { triangles } => {
+                for triangle in triangles.iter() {
+                    for &(point, color) in triangle.0.iter() {
+                        vertices.push(Vertex {
+                            position: to_ndc(point),
+                            uv: [0.0, 0.0],
+                            color: linear_color(color),
+                            mode: MODE_GEOMETRY,
+                        });
+                    }
+                }
+            }
+            PrimitiveKind::Image {
+                image_id,
+                color,
+                source_rect,
+            } => {
+                let (image_width, image_height) = match image_map.get(&image_id) {
+                    Some(image) => image.dimensions(),
+                    None => return,
+                };
+
+                // `source_rect` is in pixels from the bottom left of the image, whereas texture
+                // coordinates start from the top left.
+                let uv_rect = match source_rect {
+                    Some(source_rect) => {
+                        let (left, right, bottom, top) = source_rect.l_r_b_t();
+                        let width = image_width as f64;
+                        let height = image_height as f64;
+                        [
+                            [(left / width) as f32, (1.0 - top / height) as f32],
+                            [(right / width) as f32, (1.0 - bottom / height) as f32],
+                        ]
+                    }
+                    None => [[0.0, 0.0], [1.0, 1.0]],
+                };
+
+                let color = color
+                    .map(|color| color.to_rgb())
+                    .unwrap_or(Rgba(1.0, 1.0, 1.0, 1.0));
+
+                push_quad(
+                    vertices,
+                    rect_to_ndc(&primitive.rect),
+                    uv_rect,
+                    linear_color(color),
+                    MODE_IMAGE,
+                );
+
+                image = Some(image_id);
+            }
+            PrimitiveKind::Text {
+                color,
+                text,
+                font_id,
+            } => {
+                let color = linear_color(color.to_rgb());
+
+                let physical_size = [window_size[0] * dpi_factor, window_size[1] * dpi_factor];
+
+                let pixels_to_ndc = |x: i32, y: i32| -> [f32; 2] {
+                    [
+                        (x as f64 * 2.0 / physical_size[0] - 1.0) as f32,
+                        (y as f64 * 2.0 / physical_size[1] - 1.0) as f32,
+                    ]
+                };
+
+                for (uv_rect, screen_rect) in
+                    glyph_atlas.text_rects(text, font_id, dpi_factor as f32)
+                {
+                    push_quad(
+                        vertices,
+                        [
+                            pixels_to_ndc(screen_rect.min.x, screen_rect.min.y),
+                            pixels_to_ndc(screen_rect.max.x, screen_rect.max.y),
+                        ],
+                        [
+                            [uv_rect.min.x, uv_rect.min.y],
+                            [uv_rect.max.x, uv_rect.max.y],
+                        ],
+                        color,
+                        MODE_TEXT,
+                    );
+                }
+            }
+            PrimitiveKind::Other(_) => (),
+        }
+    }
+
+    draw_list.record(start, image, primitive.scizzor);
+}
+
+/// Appends two triangles covering the quad from `position_rect[0]` at its top left to
+/// `position_rect[1]` at its bottom right, with `uv_rect` mapped to the same corners.
+fn push_quad(
+    vertices: &mut Vec<Vertex>,
+    position_rect: [[f32; 2]; 2],
+    uv_rect: [[f32; 2]; 2],
+    color: [f32; 4],
+    mode: u32,
+) {
+    let [[left, top], [right, bottom]] = position_rect;
+    let [[uv_left, uv_top], [uv_right, uv_bottom]] = uv_rect;
+
+    let vertex = |x, y, u, v| Vertex {
+        position: [x, y],
+        uv: [u, v],
+        color,
+        mode,
+    };
+
+    vertices.push(vertex(left, top, uv_left, uv_top));
+    vertices.push(vertex(right, bottom, uv_right, uv_bottom));
+    vertices.push(vertex(left, bottom, uv_left, uv_bottom));
+    vertices.push(vertex(left, top, uv_left, uv_top));
+    vertices.push(vertex(right, top, uv_right, uv_top));
+    vertices.push(vertex(right, bottom, uv_right, uv_bottom));
+}
+
+/// Converts a conrod color, which is specified in sRGB space, into linear space for blending into
+/// an sRGB framebuffer.
+pub fn linear_color(Rgba(r, g, b, a): Rgba) -> [f32; 4] {
+    [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+}
+
+/// Decodes a single sRGB-encoded color component in the range 0 to 1.
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes a single linear color component in the range 0 to 1 as sRGB.
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use conrod::widget;
+    use conrod::widget::triangles::Triangle;
+
+    const WINDOW_SIZE: [f64; 2] = [200.0, 100.0];
+
+    struct TestImage(u32, u32);
+
+    impl ImageDimensions for TestImage {
+        fn dimensions(&self) -> (u32, u32) {
+            (self.0, self.1)
+        }
+    }
+
+    /// Renders a single primitive covering `rect` in a `Ui` of size `WINDOW_SIZE`.
+    fn render(kind: PrimitiveKind, rect: Rect, image_map: &image::Map<TestImage>) -> DrawList {
+        let mut draw_list = DrawList::new();
+
+        render_primitive(
+            &mut draw_list,
+            &GlyphAtlas::new(64, 64),
+            image_map,
+            Primitive {
+                id: widget::Id::new(0),
+                kind,
+                scizzor: rect,
+                rect,
+            },
+            WINDOW_SIZE,
+            1.0,
+        );
+
+        draw_list
+    }
+
+    #[test]
+    fn rectangle_is_two_triangles_in_flipped_ndc() {
+        let rect = Rect::from_corners([0.0, 0.0], [100.0, 50.0]);
+        let color = Rgba(1.0, 0.5, 0.25, 0.75);
+
+        let draw_list = render(
+            PrimitiveKind::Rectangle {
+                color: Color::Rgba(color.0, color.1, color.2, color.3),
+            },
+            rect,
+            &image::Map::new(),
+        );
+
+        // The top of the rectangle is at the top of the window, which is at -1 in y.
+        let positions: Vec<_> = draw_list.vertices.iter().map(|v| v.position).collect();
+        assert_eq!(
+            positions,
+            vec![
+                [0.0, -1.0],
+                [1.0, 0.0],
+                [0.0, 0.0],
+                [0.0, -1.0],
+                [1.0, -1.0],
+                [1.0, 0.0],
+            ]
+        );
+
+        for vertex in &draw_list.vertices {
+            assert_eq!(vertex.color, linear_color(color));
+            assert_eq!(vertex.mode, MODE_GEOMETRY);
+        }
+
+        assert_eq!(
+            draw_list.commands,
+            vec![DrawCommand {
+                image_id: None,
+                scissor: rect,
+                vertices: 0..6,
+            }]
+        );
+    }
+
+    #[test]
+    fn image_source_rect_is_mapped_to_uvs() {
+        let mut image_map = image::Map::new();
+        let image_id = image_map.insert(TestImage(64, 32));
+
+        let rect = Rect::from_corners([-50.0, -25.0], [50.0, 25.0]);
+
+        let draw_list = render(
+            PrimitiveKind::Image {
+                image_id,
+                color: None,
+                source_rect: Some(Rect::from_corners([16.0, 8.0], [48.0, 24.0])),
+            },
+            rect,
+            &image_map,
+        );
+
+        // The source rectangle is measured from the bottom of the image, and UVs from the top.
+        let uvs: Vec<_> = draw_list.vertices.iter().map(|v| v.uv).collect();
+        assert_eq!(
+            uvs,
+            vec![
+                [0.25, 0.25],
+                [0.75, 0.75],
+                [0.25, 0.75],
+                [0.25, 0.25],
+                [0.75, 0.25],
+                [0.75, 0.75],
+            ]
+        );
+
+        for vertex in &draw_list.vertices {
+            assert_eq!(vertex.color, linear_color(Rgba(1.0, 1.0, 1.0, 1.0)));
+            assert_eq!(vertex.mode, MODE_IMAGE);
+        }
+
+        assert_eq!(draw_list.commands[0].image_id, Some(image_id));
+    }
+
+    #[test]
+    fn multicolor_triangle_keeps_its_vertex_colors() {
+        let colors = [
+            Rgba(1.0, 0.0, 0.0, 1.0),
+            Rgba(0.0, 0.5, 0.0, 0.5),
+            Rgba(0.0, 0.0, 0.25, 0.25),
+        ];
+
+        let triangles = [Triangle([
+            ([-100.0, -50.0], colors[0]),
+            ([100.0, -50.0], colors[1]),
+            ([0.0, 50.0], colors[2]),
+        ])];
+
+        let draw_list = render(
+            PrimitiveKind::TrianglesMultiColor {
+                triangles: &triangles,
+            },
+            Rect::from_corners([-100.0, -50.0], [100.0, 50.0]),
+            &image::Map::new(),
+        );
+
+        let vertex_colors: Vec<_> = draw_list.vertices.iter().map(|v| v.color).collect();
+        assert_eq!(
+            vertex_colors,
+            colors
+                .iter()
+                .map(|&color| linear_color(color))
+                .collect::<Vec<_>>()
+        );
+
+        let positions: Vec<_> = draw_list.vertices.iter().map(|v| v.position).collect();
+        assert_eq!(positions, vec![[-1.0, 1.0], [1.0, 1.0], [0.0, -1.0]]);
+    }
+
+    #[test]
+    fn centred_scissor_maps_to_the_middle_of_the_framebuffer() {
+        let extent = Extent2D {
+            width: 200,
+            height: 100,
+        };
+
+        assert_eq!(
+            scissor_to_framebuffer(
+                &Rect::from_corners([-50.0, -25.0], [50.0, 25.0]),
+                WINDOW_SIZE,
+                extent
+            ),
+            PsoRect {
+                x: 50,
+                y: 25,
+                w: 100,
+                h: 50,
+            }
+        );
+    }
+
+    #[test]
+    fn scissor_partly_off_screen_is_clamped_to_the_extent() {
+        let extent = Extent2D {
+            width: 200,
+            height: 100,
+        };
+
+        assert_eq!(
+            scissor_to_framebuffer(
+                &Rect::from_corners([-150.0, -25.0], [0.0, 75.0]),
+                WINDOW_SIZE,
+                extent
+            ),
+            PsoRect {
+                x: 0,
+                y: 0,
+                w: 100,
+                h: 75,
+            }
+        );
+    }
+
+    #[test]
+    fn scissor_is_scaled_by_the_dpi_factor() {
+        let extent = Extent2D {
+            width: 400,
+            height: 200,
+        };
+
+        assert_eq!(
+            scissor_to_framebuffer(
+                &Rect::from_corners([0.0, 0.0], [50.0, 25.0]),
+                WINDOW_SIZE,
+                extent
+            ),
+            PsoRect {
+                x: 200,
+                y: 50,
+                w: 100,
+                h: 50,
+            }
+        );
+    }
+
+    /// Appends six vertices of `mode`, as if for a quad, and returns the index of the first.
+    fn push_vertices(draw_list: &mut DrawList, mode: u32) -> usize {
+        let start = draw_list.vertices.len();
+
+        push_quad(
+            &mut draw_list.vertices,
+            [[-1.0, -1.0], [1.0, 1.0]],
+            [[0.0, 0.0], [1.0, 1.0]],
+            [1.0, 1.0, 1.0, 1.0],
+            mode,
+        );
+
+        start
+    }
+
+    #[test]
+    fn geometry_and_text_share_a_command() {
+        let scissor = Rect::from_corners([-100.0, -50.0], [100.0, 50.0]);
+        let mut draw_list = DrawList::new();
+
+        let start = push_vertices(&mut draw_list, MODE_GEOMETRY);
+        draw_list.record(start, None, scissor);
+        let start = push_vertices(&mut draw_list, MODE_TEXT);
+        draw_list.record(start, None, scissor);
+
+        assert_eq!(
+            draw_list.commands,
+            vec![DrawCommand {
+                image_id: None,
+                scissor,
+                vertices: 0..12,
+            }]
+        );
+    }
+
+    #[test]
+    fn different_images_split_the_command() {
+        let mut image_map = image::Map::new();
+        let first = image_map.insert(TestImage(1, 1));
+        let second = image_map.insert(TestImage(1, 1));
+
+        let scissor = Rect::from_corners([-100.0, -50.0], [100.0, 50.0]);
+        let mut draw_list = DrawList::new();
+
+        let start = push_vertices(&mut draw_list, MODE_IMAGE);
+        draw_list.record(start, Some(first), scissor);
+        let start = push_vertices(&mut draw_list, MODE_IMAGE);
+        draw_list.record(start, Some(second), scissor);
+
+        assert_eq!(
+            draw_list.commands,
+            vec![
+                DrawCommand {
+                    image_id: Some(first),
+                    scissor,
+                    vertices: 0..6,
+                },
+                DrawCommand {
+                    image_id: Some(second),
+                    scissor,
+                    vertices: 6..12,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn vertices_without_an_image_join_an_image_command() {
+        let mut image_map = image::Map::new();
+        let image_id = image_map.insert(TestImage(1, 1));
+
+        let scissor = Rect::from_corners([-100.0, -50.0], [100.0, 50.0]);
+        let mut draw_list = DrawList::new();
+
+        let start = push_vertices(&mut draw_list, MODE_GEOMETRY);
+        draw_list.record(start, None, scissor);
+        let start = push_vertices(&mut draw_list, MODE_IMAGE);
+        draw_list.record(start, Some(image_id), scissor);
+        let start = push_vertices(&mut draw_list, MODE_TEXT);
+        draw_list.record(start, None, scissor);
+
+        assert_eq!(
+            draw_list.commands,
+            vec![DrawCommand {
+                image_id: Some(image_id),
+                scissor,
+                vertices: 0..18,
+            }]
+        );
+    }
+
+    #[test]
+    fn scissor_change_splits_the_command() {
+        let first = Rect::from_corners([-100.0, -50.0], [100.0, 50.0]);
+        let second = Rect::from_corners([-50.0, -25.0], [50.0, 25.0]);
+        let mut draw_list = DrawList::new();
+
+        let start = push_vertices(&mut draw_list, MODE_GEOMETRY);
+        draw_list.record(start, None, first);
+        let start = push_vertices(&mut draw_list, MODE_GEOMETRY);
+        draw_list.record(start, None, second);
+
+        assert_eq!(
+            draw_list.commands,
+            vec![
+                DrawCommand {
+                    image_id: None,
+                    scissor: first,
+                    vertices: 0..6,
+                },
+                DrawCommand {
+                    image_id: None,
+                    scissor: second,
+                    vertices: 6..12,
+                },
+            ]
+        );
     }
 }
@@ -0,0 +1,492 @@
+use conrod::color::Color;
+use conrod::image;
+use conrod::render::Primitives;
+use gfx_hal::pso::Rect as PsoRect;
+use gfx_hal::window::Extent2D;
+use glyph_cache::GlyphAtlas;
+use image::RgbaImage;
+use renderer::{
+    linear_color, linear_to_srgb, render_primitives, scissor_to_framebuffer, srgb_to_linear,
+    AlphaMode, DrawList, DrawStats, ImageDimensions, Vertex, GLYPH_CACHE_SIZE, MODE_IMAGE,
+    MODE_TEXT,
+};
+
+impl ImageDimensions for RgbaImage {
+    fn dimensions(&self) -> (u32, u32) {
+        RgbaImage::dimensions(self)
+    }
+}
+
+/// Rasterizes conrod primitives on the CPU, as a reference for the output of `Renderer`.
+///
+/// Primitives are turned into the same `DrawList` as the GPU path, and its triangles are then
+/// filled following the same rules as the conrod shaders and pipeline: linear color blending
+/// into an sRGB image, bilinear texture filtering clamped to the edge, and the top-left fill
+/// convention.
+pub struct SoftwareRenderer {
+    glyph_atlas: GlyphAtlas,
+    /// The R8 texels of the glyph cache.
+    glyph_cache: Vec<u8>,
+    draw_list: DrawList,
+    extent: Extent2D,
+    alpha_mode: AlphaMode,
+    clear_color: [f32; 4],
+}
+
+impl SoftwareRenderer {
+    pub fn new(extent: Extent2D, alpha_mode: AlphaMode) -> SoftwareRenderer {
+        SoftwareRenderer {
+            glyph_atlas: GlyphAtlas::new(GLYPH_CACHE_SIZE, GLYPH_CACHE_SIZE),
+            glyph_cache: vec![0; (GLYPH_CACHE_SIZE * GLYPH_CACHE_SIZE) as usize],
+            draw_list: DrawList::new(),
+            extent,
+            alpha_mode,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    /// Sets the size of the images returned by `render`.
+    pub fn resize(&mut self, extent: Extent2D) {
+        self.extent = extent;
+    }
+
+    /// Sets the color that images are cleared to before drawing.
+    pub fn set_clear_color(&mut self, color: Color) {
+        self.clear_color = linear_color(color.to_rgb());
+    }
+
+    /// The vertices and draw commands produced by the last `render`.
+    pub fn draw_list(&self) -> &DrawList {
+        &self.draw_list
+    }
+
+    pub fn stats(&self) -> DrawStats {
+        self.draw_list.stats()
+    }
+
+    /// Rasterizes `primitives` into a new image.
+    ///
+    /// `dpi_factor` is the number of image pixels per unit of the conrod `Ui`.
+    pub fn render(
+        &mut self,
+        primitives: Primitives,
+        image_map: &image::Map<RgbaImage>,
+        dpi_factor: f64,
+    ) -> RgbaImage {
+        let window_size = [
+            self.extent.width as f64 / dpi_factor,
+            self.extent.height as f64 / dpi_factor,
+        ];
+
+        render_primitives(
+            &mut self.draw_list,
+            &mut self.glyph_atlas,
+            image_map,
+            primitives,
+            window_size,
+            dpi_factor,
+        );
+
+        for region in self.glyph_atlas.take_dirty_regions() {
+            for row in 0..region.height {
+                let src = (row * region.width) as usize;
+                let dst = ((region.y + row) * GLYPH_CACHE_SIZE + region.x) as usize;
+                self.glyph_cache[dst..dst + region.width as usize]
+                    .copy_from_slice(&region.data[src..src + region.width as usize]);
+            }
+        }
+
+        let mut target = Target {
+            pixels: vec![self.clear_color; (self.extent.width * self.extent.height) as usize],
+            width: self.extent.width,
+            height: self.extent.height,
+        };
+
+        for command in &self.draw_list.commands {
+            let scissor = scissor_to_framebuffer(&command.scissor, window_size, self.extent);
+
+            if scissor.w == 0 || scissor.h == 0 {
+                continue;
+            }
+
+            let image = command
+                .image_id
+                .and_then(|image_id| image_map.get(&image_id));
+
+            let vertices = &self.draw_list.vertices
+                [command.vertices.start as usize..command.vertices.end as usize];
+
+            for triangle in vertices.chunks(3).filter(|triangle| triangle.len() == 3) {
+                self.fill_triangle(&mut target, triangle, &scissor, image);
+            }
+        }
+
+        let mut data = Vec::with_capacity(target.pixels.len() * 4);
+
+        for pixel in &target.pixels {
+            data.extend(pixel.iter().take(3).map(|&c| to_unorm8(linear_to_srgb(c))));
+            data.push(to_unorm8(pixel[3]));
+        }
+
+        RgbaImage::from_raw(self.extent.width, self.extent.height, data).unwrap()
+    }
+
+    fn fill_triangle(
+        &self,
+        target: &mut Target,
+        triangle: &[Vertex],
+        scissor: &PsoRect,
+        image: Option<&RgbaImage>,
+    ) {
+        let (width, height) = (target.width as f64, target.height as f64);
+
+        // Vertex positions are in normalized device coordinates with y pointing down.
+        let to_pixels = |vertex: &Vertex| -> [f64; 2] {
+            [
+                (vertex.position[0] as f64 + 1.0) * width / 2.0,
+                (vertex.position[1] as f64 + 1.0) * height / 2.0,
+            ]
+        };
+
+        let mut v = [&triangle[0], &triangle[1], &triangle[2]];
+        let mut p = [to_pixels(v[0]), to_pixels(v[1]), to_pixels(v[2])];
+
+        let area = edge(p[0], p[1], p[2]);
+
+        if area == 0.0 {
+            return;
+        }
+
+        // Nothing is culled, so put the vertices in a consistent winding order.
+        if area < 0.0 {
+            v.swap(1, 2);
+            p.swap(1, 2);
+        }
+
+        let area = area.abs();
+
+        // The mode is a flat input, taken from the provoking (first) vertex.
+        let mode = triangle[0].mode;
+
+        let min = |axis: usize| p[0][axis].min(p[1][axis]).min(p[2][axis]).floor() as i32;
+        let max = |axis: usize| p[0][axis].max(p[1][axis]).max(p[2][axis]).ceil() as i32;
+
+        let x0 = min(0).max(scissor.x as i32);
+        let x1 = max(0).min(scissor.x as i32 + scissor.w as i32);
+        let y0 = min(1).max(scissor.y as i32);
+        let y1 = max(1).min(scissor.y as i32 + scissor.h as i32);
+
+        let edges = [(1, 2), (2, 0), (0, 1)];
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let centre = [x as f64 + 0.5, y as f64 + 0.5];
+
+                let mut weights = [0.0; 3];
+                let mut inside = true;
+
+                for (i, &(a, b)) in edges.iter().enumerate() {
+                    let w = edge(p[a], p[b], centre);
+
+                    if w < 0.0 || (w == 0.0 && !is_top_left(p[a], p[b])) {
+                        inside = false;
+                        break;
+                    }
+
+                    weights[i] = (w / area) as f32;
+                }
+
+                if !inside {
+                    continue;
+                }
+
+                let mut color = [0.0; 4];
+                let mut uv = [0.0; 2];
+
+                for (vertex, weight) in v.iter().zip(weights.iter()) {
+                    for (value, attribute) in color.iter_mut().zip(vertex.color.iter()) {
+                        *value += attribute * weight;
+                    }
+                    for (value, attribute) in uv.iter_mut().zip(vertex.uv.iter()) {
+                        *value += attribute * weight;
+                    }
+                }
+
+                let mut source = if mode == MODE_TEXT {
+                    let coverage = sample(GLYPH_CACHE_SIZE, GLYPH_CACHE_SIZE, uv, |x, y| {
+                        let texel = self.glyph_cache[(y * GLYPH_CACHE_SIZE + x) as usize];
+                        [texel as f32 / 255.0, 0.0, 0.0, 1.0]
+                    });
+                    [color[0], color[1], color[2], color[3] * coverage[0]]
+                } else if mode == MODE_IMAGE {
+                    let texel = match image {
+                        Some(image) => sample(image.width(), image.height(), uv, |x, y| {
+                            let texel = image.get_pixel(x, y).data;
+                            [
+                                srgb_to_linear(texel[0] as f32 / 255.0),
+                                srgb_to_linear(texel[1] as f32 / 255.0),
+                                srgb_to_linear(texel[2] as f32 / 255.0),
+                                texel[3] as f32 / 255.0,
+                            ]
+                        }),
+                        None => [0.0; 4],
+                    };
+                    [
+                        texel[0] * color[0],
+                        texel[1] * color[1],
+                        texel[2] * color[2],
+                        texel[3] * color[3],
+                    ]
+                } else {
+                    color
+                };
+
+                if self.alpha_mode == AlphaMode::Premultiplied {
+                    let alpha = source[3];
+                    for value in source.iter_mut().take(3) {
+                        *value *= alpha;
+                    }
+                }
+
+                target.blend(x as u32, y as u32, source, self.alpha_mode);
+            }
+        }
+    }
+}
+
+/// The linear RGBA pixels being drawn into by a `SoftwareRenderer`.
+struct Target {
+    pixels: Vec<[f32; 4]>,
+    width: u32,
+    height: u32,
+}
+
+impl Target {
+    /// Blends `source` into the pixel at `x`, `y` in the same way as the blend state of the
+    /// pipeline built for `alpha_mode`.
+    fn blend(&mut self, x: u32, y: u32, source: [f32; 4], alpha_mode: AlphaMode) {
+        let destination = &mut self.pixels[(y * self.width + x) as usize];
+
+        let source_factor = match alpha_mode {
+            AlphaMode::Straight => source[3],
+            AlphaMode::Premultiplied => 1.0,
+        };
+
+        for (value, source_value) in destination.iter_mut().zip(source.iter()).take(3) {
+            *value = source_value * source_factor + *value * (1.0 - source[3]);
+        }
+
+        destination[3] = source[3] + destination[3] * (1.0 - source[3]);
+    }
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `c`, which is positive when `c` is to the
+/// right of the line from `a` to `b` in y-down coordinates.
+fn edge(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Whether the edge from `a` to `b` of a triangle with positive area is a top or left edge, on
+/// which pixel centres are inside the triangle.
+fn is_top_left(a: [f64; 2], b: [f64; 2]) -> bool {
+    let dx = b[0] - a[0];
+    let dy = b[1] - a[1];
+    dy < 0.0 || (dy == 0.0 && dx > 0.0)
+}
+
+/// Samples a `width` by `height` texture at `uv` with bilinear filtering, clamping to the edge.
+fn sample<F>(width: u32, height: u32, uv: [f32; 2], texel: F) -> [f32; 4]
+where
+    F: Fn(u32, u32) -> [f32; 4],
+{
+    let x = uv[0] * width as f32 - 0.5;
+    let y = uv[1] * height as f32 - 0.5;
+
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let fx = x - x0;
+    let fy = y - y0;
+
+    let clamp = |value: f32, size: u32| value.max(0.0).min(size as f32 - 1.0) as u32;
+
+    let (left, right) = (clamp(x0, width), clamp(x0 + 1.0, width));
+    let (top, bottom) = (clamp(y0, height), clamp(y0 + 1.0, height));
+
+    let texels = [
+        texel(left, top),
+        texel(right, top),
+        texel(left, bottom),
+        texel(right, bottom),
+    ];
+
+    let mut result = [0.0; 4];
+
+    for (c, value) in result.iter_mut().enumerate() {
+        let upper = texels[0][c] * (1.0 - fx) + texels[1][c] * fx;
+        let lower = texels[2][c] * (1.0 - fx) + texels[3][c] * fx;
+        *value = upper * (1.0 - fy) + lower * fy;
+    }
+
+    result
+}
+
+fn to_unorm8(c: f32) -> u8 {
+    (c.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use renderer::MODE_GEOMETRY;
+
+    const EXTENT: Extent2D = Extent2D {
+        width: 4,
+        height: 4,
+    };
+
+    fn target(color: [f32; 4]) -> Target {
+        Target {
+            pixels: vec![color; (EXTENT.width * EXTENT.height) as usize],
+            width: EXTENT.width,
+            height: EXTENT.height,
+        }
+    }
+
+    /// A geometry triangle with corners at `points`, in pixels of a target of size `EXTENT`.
+    fn triangle(points: [[f32; 2]; 3], color: [f32; 4]) -> Vec<Vertex> {
+        points
+            .iter()
+            .map(|point| Vertex {
+                position: [
+                    point[0] * 2.0 / EXTENT.width as f32 - 1.0,
+                    point[1] * 2.0 / EXTENT.height as f32 - 1.0,
+                ],
+                uv: [0.0, 0.0],
+                color,
+                mode: MODE_GEOMETRY,
+            })
+            .collect()
+    }
+
+    /// Fills the two triangles of the rectangle from `min` to `max`, split along the diagonal
+    /// from its top right to its bottom left corner.
+    fn fill_rectangle(
+        target: &mut Target,
+        min: [f32; 2],
+        max: [f32; 2],
+        color: [f32; 4],
+        scissor: &PsoRect,
+    ) {
+        let renderer = SoftwareRenderer::new(EXTENT, AlphaMode::Straight);
+
+        for points in &[
+            [min, [max[0], min[1]], [min[0], max[1]]],
+            [[max[0], min[1]], max, [min[0], max[1]]],
+        ] {
+            renderer.fill_triangle(target, &triangle(*points, color), scissor, None);
+        }
+    }
+
+    fn full_scissor() -> PsoRect {
+        PsoRect {
+            x: 0,
+            y: 0,
+            w: EXTENT.width as i16,
+            h: EXTENT.height as i16,
+        }
+    }
+
+    /// The alpha of each pixel of `target`, row by row.
+    fn alphas(target: &Target) -> Vec<Vec<f32>> {
+        target
+            .pixels
+            .chunks(target.width as usize)
+            .map(|row| row.iter().map(|pixel| pixel[3]).collect())
+            .collect()
+    }
+
+    #[test]
+    fn fill_covers_pixel_centres_on_top_and_left_edges_once() {
+        let mut target = target([0.0; 4]);
+
+        // Every edge of the rectangle, and the diagonal shared by its triangles, passes through
+        // pixel centres. Half transparent white shows pixels covered twice as 0.75 alpha.
+        fill_rectangle(
+            &mut target,
+            [0.5, 0.5],
+            [2.5, 2.5],
+            [1.0, 1.0, 1.0, 0.5],
+            &full_scissor(),
+        );
+
+        assert_eq!(
+            alphas(&target),
+            vec![
+                vec![0.5, 0.5, 0.0, 0.0],
+                vec![0.5, 0.5, 0.0, 0.0],
+                vec![0.0, 0.0, 0.0, 0.0],
+                vec![0.0, 0.0, 0.0, 0.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn fill_is_clipped_to_the_scissor() {
+        let mut target = target([0.0; 4]);
+
+        fill_rectangle(
+            &mut target,
+            [0.0, 0.0],
+            [4.0, 4.0],
+            [1.0; 4],
+            &PsoRect {
+                x: 1,
+                y: 1,
+                w: 2,
+                h: 2,
+            },
+        );
+
+        assert_eq!(
+            alphas(&target),
+            vec![
+                vec![0.0, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 1.0, 0.0],
+                vec![0.0, 1.0, 1.0, 0.0],
+                vec![0.0, 0.0, 0.0, 0.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn straight_and_premultiplied_blends_agree_for_equivalent_sources() {
+        let mut straight = target([0.0, 0.0, 1.0, 1.0]);
+        straight.blend(0, 0, [1.0, 0.0, 0.0, 0.5], AlphaMode::Straight);
+
+        let mut premultiplied = target([0.0, 0.0, 1.0, 1.0]);
+        premultiplied.blend(0, 0, [0.5, 0.0, 0.0, 0.5], AlphaMode::Premultiplied);
+
+        assert_eq!(straight.pixels[0], [0.5, 0.0, 0.5, 1.0]);
+        assert_eq!(premultiplied.pixels[0], [0.5, 0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn premultiplied_blend_does_not_scale_the_source_color() {
+        let mut target = target([0.0; 4]);
+        target.blend(0, 0, [1.0, 0.0, 0.0, 0.5], AlphaMode::Premultiplied);
+
+        assert_eq!(target.pixels[0], [1.0, 0.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn sample_filters_bilinearly_and_clamps_to_the_edge() {
+        // A 2 by 1 texture whose red channel is 0 in the left texel and 1 in the right one.
+        let sample_at = |u| sample(2, 1, [u, 0.5], |x, _| [x as f32, 0.0, 0.0, 1.0]);
+
+        assert_eq!(sample_at(0.0), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(sample_at(0.25), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(sample_at(0.5), [0.5, 0.0, 0.0, 1.0]);
+        assert_eq!(sample_at(0.75), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(sample_at(1.0), [1.0, 0.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,567 @@
+use buffer::{
+    build_host_buffer, destroy_host_buffer, find_memory_type, write_host_buffer, HostBuffer,
+};
+use error::Error;
+use gfx_hal::buffer::Usage as BufferUsage;
+use gfx_hal::command::{BufferImageCopy, ClearColor, ClearDepthStencil, CommandBuffer, OneShot};
+use gfx_hal::device::Device;
+use gfx_hal::format::{Aspects, Format, Swizzle};
+use gfx_hal::image::{
+    Access, Extent, Filter, Kind, Layout, Offset, SamplerInfo, SubresourceLayers, SubresourceRange,
+    Tiling, Usage, ViewCapabilities, ViewKind, WrapMode,
+};
+use gfx_hal::memory::{Barrier, Dependencies, Properties};
+use gfx_hal::pool::CommandPool;
+use gfx_hal::pso::{
+    Descriptor, DescriptorPool, DescriptorRangeDesc, DescriptorSetWrite, DescriptorType,
+    PipelineStage,
+};
+use gfx_hal::queue::capability::Graphics;
+use gfx_hal::queue::{CommandQueue, Submission};
+use gfx_hal::Backend;
+use gfx_hal::MemoryType;
+use image;
+use renderer::ImageDimensions;
+use std::ops::Range;
+use std::path::Path;
+
+const COLOR_RANGE: SubresourceRange = SubresourceRange {
+    aspects: Aspects::COLOR,
+    levels: 0..1,
+    layers: 0..1,
+};
+
+/// A sampled 2D texture, along with the descriptor set that binds it to the conrod shaders.
+pub struct Texture<B: Backend> {
+    image: <B as gfx_hal::Backend>::Image,
+    memory: <B as gfx_hal::Backend>::Memory,
+    view: <B as gfx_hal::Backend>::ImageView,
+    sampler: <B as gfx_hal::Backend>::Sampler,
+    descriptor_pool: <B as gfx_hal::Backend>::DescriptorPool,
+    pub descriptor_set: <B as gfx_hal::Backend>::DescriptorSet,
+    format: Format,
+    width: u32,
+    height: u32,
+    /// False until the first upload or clear, while the contents of the image are undefined.
+    initialized: bool,
+}
+
+impl<B: Backend> ImageDimensions for Texture<B> {
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+/// A rectangle of tightly packed texel data to be copied into a texture.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+/// Builds a 2D image of `format` in device-local memory, and a view of it.
+///
+/// If any step fails, whatever was already built is destroyed.
+pub fn build_image<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    memory_types: &[MemoryType],
+    format: Format,
+    width: u32,
+    height: u32,
+    usage: Usage,
+) -> Result<
+    (
+        <B as gfx_hal::Backend>::Image,
+        <B as gfx_hal::Backend>::Memory,
+        <B as gfx_hal::Backend>::ImageView,
+    ),
+    Error,
+> {
+    let unbound_image = device.create_image(
+        Kind::D2(width, height, 1, 1),
+        1,
+        format,
+        Tiling::Optimal,
+        usage,
+        ViewCapabilities::empty(),
+    )?;
+
+    let requirements = device.get_image_requirements(&unbound_image);
+
+    let memory_type = find_memory_type(
+        memory_types,
+        requirements.type_mask,
+        Properties::DEVICE_LOCAL,
+    )
+    .ok_or(Error::NoMemoryType)?;
+
+    let memory = device.allocate_memory(memory_type, requirements.size)?;
+
+    let image = match device.bind_image_memory(&memory, 0, unbound_image) {
+        Ok(image) => image,
+        Err(error) => {
+            device.free_memory(memory);
+            return Err(error.into());
+        }
+    };
+
+    match device.create_image_view(
+        &image,
+        ViewKind::D2,
+        format,
+        Swizzle::NO,
+        COLOR_RANGE.clone(),
+    ) {
+        Ok(view) => Ok((image, memory, view)),
+        Err(error) => {
+            device.destroy_image(image);
+            device.free_memory(memory);
+            Err(error.into())
+        }
+    }
+}
+
+/// Builds a device-local texture and a descriptor set for it laid out according to `set_layout`.
+///
+/// The contents of the texture are undefined until they are uploaded with
+/// `upload_texture_regions` or cleared with `record_texture_clear`.
+pub fn build_texture<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    memory_types: &[MemoryType],
+    set_layout: &<B as gfx_hal::Backend>::DescriptorSetLayout,
+    format: Format,
+    width: u32,
+    height: u32,
+) -> Result<Texture<B>, Error> {
+    let (image, memory, view) = build_image::<B>(
+        device,
+        memory_types,
+        format,
+        width,
+        height,
+        Usage::TRANSFER_DST | Usage::SAMPLED,
+    )?;
+
+    let sampler = device.create_sampler(SamplerInfo::new(Filter::Linear, WrapMode::Clamp));
+
+    let mut descriptor_pool = device.create_descriptor_pool(
+        1,
+        &[DescriptorRangeDesc {
+            ty: DescriptorType::CombinedImageSampler,
+            count: 1,
+        }],
+    );
+
+    let descriptor_set = match descriptor_pool.allocate_set(set_layout) {
+        Ok(descriptor_set) => descriptor_set,
+        Err(error) => {
+            device.destroy_descriptor_pool(descriptor_pool);
+            device.destroy_sampler(sampler);
+            device.destroy_image_view(view);
+            device.destroy_image(image);
+            device.free_memory(memory);
+            return Err(error.into());
+        }
+    };
+
+    device.write_descriptor_sets(vec![DescriptorSetWrite {
+        set: &descriptor_set,
+        binding: 0,
+        array_offset: 0,
+        descriptors: Some(Descriptor::CombinedImageSampler(
+            &view,
+            Layout::ShaderReadOnlyOptimal,
+            &sampler,
+        )),
+    }]);
+
+    Ok(Texture {
+        image,
+        memory,
+        view,
+        sampler,
+        descriptor_pool,
+        descriptor_set,
+        format,
+        width,
+        height,
+        initialized: false,
+    })
+}
+
+pub fn destroy_texture<B: Backend>(device: &<B as gfx_hal::Backend>::Device, texture: Texture<B>) {
+    device.destroy_descriptor_pool(texture.descriptor_pool);
+    device.destroy_sampler(texture.sampler);
+    device.destroy_image_view(texture.view);
+    device.destroy_image(texture.image);
+    device.free_memory(texture.memory);
+}
+
+/// Writes the texel data of `regions` into `staging_buffer`, growing it if necessary, and returns
+/// the offset at which each region was written.
+///
+/// The GPU must have finished reading from `staging_buffer`.
+pub fn stage_texture_regions<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    memory_types: &[MemoryType],
+    texture: &Texture<B>,
+    staging_buffer: &mut HostBuffer<B>,
+    regions: &[TextureRegion],
+) -> Result<Vec<u64>, Error> {
+    let texel_size = texture.format.surface_desc().bits as usize / 8;
+
+    // Vulkan requires buffer offsets for copies to be a multiple of both 4 and the texel size.
+    let alignment = texel_size.max(4);
+
+    let mut offsets = Vec::with_capacity(regions.len());
+    let mut data = Vec::new();
+
+    for region in regions {
+        offsets.push(data.len() as u64);
+        data.extend_from_slice(&region.data);
+
+        let padded_len = (data.len() + alignment - 1) / alignment * alignment;
+        data.resize(padded_len, 0);
+    }
+
+    write_host_buffer(device, memory_types, staging_buffer, &data)?;
+
+    Ok(offsets)
+}
+
+/// Records commands that clear `texture` to zero and leave it ready to be sampled by the fragment
+/// shader, if nothing has been uploaded to it yet. A texture that is bound to a descriptor set
+/// before anything is uploaded must be cleared first, so that it is never sampled while its
+/// layout is undefined.
+pub fn record_texture_clear<B: Backend>(
+    command_buffer: &mut CommandBuffer<B, Graphics, OneShot>,
+    texture: &mut Texture<B>,
+) {
+    if texture.initialized {
+        return;
+    }
+
+    command_buffer.pipeline_barrier(
+        PipelineStage::TOP_OF_PIPE..PipelineStage::TRANSFER,
+        Dependencies::empty(),
+        &[Barrier::Image {
+            states: (Access::empty(), Layout::Undefined)
+                ..(Access::TRANSFER_WRITE, Layout::TransferDstOptimal),
+            target: &texture.image,
+            range: COLOR_RANGE.clone(),
+        }],
+    );
+
+    command_buffer.clear_image(
+        &texture.image,
+        Layout::TransferDstOptimal,
+        ClearColor::Float([0.0, 0.0, 0.0, 0.0]),
+        ClearDepthStencil(1.0, 0),
+        Some(COLOR_RANGE.clone()),
+    );
+
+    command_buffer.pipeline_barrier(
+        PipelineStage::TRANSFER..PipelineStage::FRAGMENT_SHADER,
+        Dependencies::empty(),
+        &[Barrier::Image {
+            states: (Access::TRANSFER_WRITE, Layout::TransferDstOptimal)
+                ..(Access::SHADER_READ, Layout::ShaderReadOnlyOptimal),
+            target: &texture.image,
+            range: COLOR_RANGE.clone(),
+        }],
+    );
+
+    texture.initialized = true;
+}
+
+/// Records commands that copy `regions`, previously staged at `offsets` in `staging_buffer`, into
+/// `texture` and leave it ready to be sampled by the fragment shader.
+///
+/// Where regions overlap, the later one wins. Regions not covered by `regions` keep their previous
+/// contents, except on the first upload to a texture, when they are undefined.
+pub fn record_texture_upload<B: Backend>(
+    command_buffer: &mut CommandBuffer<B, Graphics, OneShot>,
+    staging_buffer: &<B as gfx_hal::Backend>::Buffer,
+    texture: &mut Texture<B>,
+    regions: &[TextureRegion],
+    offsets: &[u64],
+) {
+    if regions.is_empty() {
+        return;
+    }
+
+    let (previous_stage, previous_state) = if texture.initialized {
+        (
+            PipelineStage::FRAGMENT_SHADER,
+            (Access::SHADER_READ, Layout::ShaderReadOnlyOptimal),
+        )
+    } else {
+        (
+            PipelineStage::TOP_OF_PIPE,
+            (Access::empty(), Layout::Undefined),
+        )
+    };
+
+    command_buffer.pipeline_barrier(
+        previous_stage..PipelineStage::TRANSFER,
+        Dependencies::empty(),
+        &[Barrier::Image {
+            states: previous_state..(Access::TRANSFER_WRITE, Layout::TransferDstOptimal),
+            target: &texture.image,
+            range: COLOR_RANGE.clone(),
+        }],
+    );
+
+    for (index, batch) in overlap_batches(regions).into_iter().enumerate() {
+        // Copies in one command may run in any order, so a region that overlaps an earlier one
+        // waits for it to finish.
+        if index > 0 {
+            command_buffer.pipeline_barrier(
+                PipelineStage::TRANSFER..PipelineStage::TRANSFER,
+                Dependencies::empty(),
+                &[Barrier::Image {
+                    states: (Access::TRANSFER_WRITE, Layout::TransferDstOptimal)
+                        ..(Access::TRANSFER_WRITE, Layout::TransferDstOptimal),
+                    target: &texture.image,
+                    range: COLOR_RANGE.clone(),
+                }],
+            );
+        }
+
+        command_buffer.copy_buffer_to_image(
+            staging_buffer,
+            &texture.image,
+            Layout::TransferDstOptimal,
+            regions[batch.clone()]
+                .iter()
+                .zip(offsets[batch].iter())
+                .map(|(region, &offset)| BufferImageCopy {
+                    buffer_offset: offset,
+                    buffer_width: region.width,
+                    buffer_height: region.height,
+                    image_layers: SubresourceLayers {
+                        aspects: Aspects::COLOR,
+                        level: 0,
+                        layers: 0..1,
+                    },
+                    image_offset: Offset {
+                        x: region.x as i32,
+                        y: region.y as i32,
+                        z: 0,
+                    },
+                    image_extent: Extent {
+                        width: region.width,
+                        height: region.height,
+                        depth: 1,
+                    },
+                }),
+        );
+    }
+
+    command_buffer.pipeline_barrier(
+        PipelineStage::TRANSFER..PipelineStage::FRAGMENT_SHADER,
+        Dependencies::empty(),
+        &[Barrier::Image {
+            states: (Access::TRANSFER_WRITE, Layout::TransferDstOptimal)
+                ..(Access::SHADER_READ, Layout::ShaderReadOnlyOptimal),
+            target: &texture.image,
+            range: COLOR_RANGE.clone(),
+        }],
+    );
+
+    texture.initialized = true;
+}
+
+/// Splits `regions` into consecutive runs in which no two regions overlap, so that each run can
+/// be copied with one command and later regions still overwrite earlier ones.
+fn overlap_batches(regions: &[TextureRegion]) -> Vec<Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+
+    for end in 0..regions.len() {
+        if regions[start..end]
+            .iter()
+            .any(|region| overlaps(region, &regions[end]))
+        {
+            batches.push(start..end);
+            start = end;
+        }
+    }
+
+    if start < regions.len() {
+        batches.push(start..regions.len());
+    }
+
+    batches
+}
+
+fn overlaps(a: &TextureRegion, b: &TextureRegion) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+/// Copies `regions` into `texture` through a temporary staging buffer, and waits for the copy to
+/// complete.
+pub fn upload_texture_regions<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    memory_types: &[MemoryType],
+    queue: &mut CommandQueue<B, Graphics>,
+    command_pool: &mut CommandPool<B, Graphics>,
+    texture: &mut Texture<B>,
+    regions: &[TextureRegion],
+) -> Result<(), Error> {
+    if regions.is_empty() {
+        return Ok(());
+    }
+
+    let mut staging_buffer = build_host_buffer(device, memory_types, BufferUsage::TRANSFER_SRC, 0)?;
+
+    let offsets =
+        match stage_texture_regions(device, memory_types, texture, &mut staging_buffer, regions) {
+            Ok(offsets) => offsets,
+            Err(error) => {
+                destroy_host_buffer(device, staging_buffer);
+                return Err(error);
+            }
+        };
+
+    let submit = {
+        let mut command_buffer = command_pool.acquire_command_buffer::<OneShot>(false);
+
+        record_texture_upload(
+            &mut command_buffer,
+            staging_buffer.buffer(),
+            texture,
+            regions,
+            &offsets,
+        );
+
+        command_buffer.finish()
+    };
+
+    let fence = device.create_fence(false);
+
+    queue.submit(Submission::new().submit(Some(submit)), Some(&fence));
+
+    device.wait_for_fence(&fence, !0);
+
+    device.destroy_fence(fence);
+    command_pool.reset();
+
+    destroy_host_buffer(device, staging_buffer);
+
+    Ok(())
+}
+
+/// Decodes a PNG or JPEG image into tightly packed RGBA8 texels, top row first.
+pub fn decode_rgba(bytes: &[u8]) -> Result<TextureRegion, image::ImageError> {
+    let image = image::load_from_memory(bytes)?.to_rgba();
+
+    Ok(TextureRegion {
+        x: 0,
+        y: 0,
+        width: image.width(),
+        height: image.height(),
+        data: image.into_raw(),
+    })
+}
+
+/// Decodes a PNG or JPEG image and uploads it into a new sRGB texture that can be inserted into
+/// the `image::Map` passed to the renderer.
+pub fn load_texture<B: Backend>(
+    device: &<B as gfx_hal::Backend>::Device,
+    memory_types: &[MemoryType],
+    queue: &mut CommandQueue<B, Graphics>,
+    command_pool: &mut CommandPool<B, Graphics>,
+    set_layout: &<B as gfx_hal::Backend>::DescriptorSetLayout,
+    bytes: &[u8],
+) -> Result<Texture<B>, Error> {
+    let region = decode_rgba(bytes)?;
+
+    let mut texture = build_texture(
+        device,
+        memory_types,
+        set_layout,
+        Format::Rgba8Srgb,
+        region.width,
+        region.height,
+    )?;
+
+    match upload_texture_regions(
+        device,
+        memory_types,
+        queue,
+        command_pool,
+        &mut texture,
+        &[region],
+    ) {
+        Ok(()) => Ok(texture),
+        Err(error) => {
+            destroy_texture(device, texture);
+            Err(error)
+        }
+    }
+}
+
+/// Like `load_texture`, but reads the image from the file at `path`.
+pub fn load_texture_from_path<B: Backend, P: AsRef<Path>>(
+    device: &<B as gfx_hal::Backend>::Device,
+    memory_types: &[MemoryType],
+    queue: &mut CommandQueue<B, Graphics>,
+    command_pool: &mut CommandPool<B, Graphics>,
+    set_layout: &<B as gfx_hal::Backend>::DescriptorSetLayout,
+    path: P,
+) -> Result<Texture<B>, Error> {
+    let bytes = ::std::fs::read(path).map_err(image::ImageError::from)?;
+
+    load_texture(
+        device,
+        memory_types,
+        queue,
+        command_pool,
+        set_layout,
+        &bytes,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(x: u32, y: u32, width: u32, height: u32) -> TextureRegion {
+        TextureRegion {
+            x,
+            y,
+            width,
+            height,
+            data: vec![0; (width * height) as usize],
+        }
+    }
+
+    #[test]
+    fn overlap_batches_keeps_disjoint_regions_together() {
+        let regions = [region(0, 0, 4, 4), region(4, 0, 4, 4), region(0, 4, 4, 4)];
+
+        assert_eq!(overlap_batches(&regions), vec![0..3]);
+    }
+
+    #[test]
+    fn overlap_batches_splits_before_an_overlapping_region() {
+        let regions = [
+            region(0, 0, 4, 4),
+            region(8, 0, 4, 4),
+            region(2, 2, 4, 4),
+            region(8, 8, 4, 4),
+            region(9, 9, 1, 1),
+        ];
+
+        assert_eq!(overlap_batches(&regions), vec![0..2, 2..4, 4..5]);
+    }
+
+    #[test]
+    fn overlap_batches_is_empty_without_regions() {
+        assert_eq!(overlap_batches(&[]), Vec::<Range<usize>>::new());
+    }
+}
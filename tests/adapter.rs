@@ -0,0 +1,71 @@
+extern crate conrod_gfx_ll;
+extern crate gfx_hal;
+
+use conrod_gfx_ll::AdapterPreference;
+use gfx_hal::adapter::{AdapterInfo, DeviceType};
+
+fn info(name: &str, vendor: usize, device: usize, device_type: DeviceType) -> AdapterInfo {
+    AdapterInfo {
+        name: name.to_string(),
+        vendor,
+        device,
+        device_type,
+    }
+}
+
+#[test]
+fn preferences_are_parsed() {
+    assert_eq!("discrete".parse(), Ok(AdapterPreference::Discrete));
+    assert_eq!("integrated".parse(), Ok(AdapterPreference::Integrated));
+    assert_eq!(
+        "name:llvmpipe".parse(),
+        Ok(AdapterPreference::Name("llvmpipe".to_string()))
+    );
+    assert_eq!(
+        "id:0x10de:7298".parse(),
+        Ok(AdapterPreference::Id {
+            vendor: 0x10de,
+            device: 7298,
+        })
+    );
+
+    for invalid in &["", "fastest", "name:", "id:0x10de", "id:nvidia:1"] {
+        assert!(
+            invalid.parse::<AdapterPreference>().is_err(),
+            "{:?} was parsed",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn preferred_device_type_ranks_first() {
+    let discrete = info("GPU", 1, 1, DeviceType::DiscreteGpu);
+    let integrated = info("GPU", 2, 2, DeviceType::IntegratedGpu);
+    let cpu = info("llvmpipe (LLVM 6.0, 256 bits)", 3, 3, DeviceType::Cpu);
+
+    let discrete_preference = AdapterPreference::Discrete;
+    assert!(discrete_preference.rank(&discrete) < discrete_preference.rank(&integrated));
+    assert!(discrete_preference.rank(&integrated) < discrete_preference.rank(&cpu));
+
+    let integrated_preference = AdapterPreference::Integrated;
+    assert!(integrated_preference.rank(&integrated) < integrated_preference.rank(&discrete));
+    assert!(integrated_preference.rank(&discrete) < integrated_preference.rank(&cpu));
+}
+
+#[test]
+fn name_and_id_preferences_only_match_their_adapter() {
+    let gpu = info("GeForce GTX 1080", 0x10de, 0x1b80, DeviceType::DiscreteGpu);
+    let cpu = info("llvmpipe (LLVM 6.0, 256 bits)", 0x10005, 0, DeviceType::Cpu);
+
+    let name = AdapterPreference::Name("LLVMpipe".to_string());
+    assert!(name.rank(&cpu).is_some());
+    assert!(name.rank(&gpu).is_none());
+
+    let id = AdapterPreference::Id {
+        vendor: 0x10de,
+        device: 0x1b80,
+    };
+    assert!(id.rank(&gpu).is_some());
+    assert!(id.rank(&cpu).is_none());
+}
@@ -0,0 +1,39 @@
+//! Builds the demo GUI for tests, without a window.
+
+// Not every test uses every helper.
+#![allow(dead_code)]
+
+use conrod_gfx_ll::Playback;
+use gui;
+use image;
+use image::RgbaImage;
+use std::time::Duration;
+
+/// The time between updates of the GUI in nanoseconds, as at 60 frames per second.
+pub const FRAME_NANOS: u32 = 16_666_667;
+
+/// The demo GUI and its state, with the Rust logo decoded into an image.
+pub type Demo = gui::Demo<RgbaImage>;
+
+/// Builds the demo GUI in a window of `width` by `height`, with the random number generator
+/// seeded with `seed`, and updates it until its layout has settled.
+pub fn build_demo(width: f64, height: f64, seed: u64) -> Demo {
+    let rust_logo = image::load_from_memory(include_bytes!("../../examples/demo/rust.png"))
+        .unwrap()
+        .to_rgba();
+
+    Demo::new([width, height], rust_logo, Some(seed))
+}
+
+/// Updates `demo` once per frame of `playback` until every input has been handled.
+pub fn play(demo: &mut Demo, mut playback: Playback) {
+    let frame = Duration::new(0, FRAME_NANOS);
+
+    while !playback.is_finished() {
+        playback.step(&mut demo.ui);
+        demo.update(frame);
+    }
+
+    // Let the GUI respond to the last input.
+    demo.update(frame);
+}
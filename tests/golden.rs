@@ -0,0 +1,139 @@
+//! Renders the demo GUI with the CPU reference rasterizer and compares the result against the
+//! golden images in `tests/golden`.
+//!
+//! Run the tests with `UPDATE_GOLDENS=1` to write the golden images from the current output, after
+//! an intended change in the output or to add a new one. Without it, a missing golden image fails
+//! the test. When a comparison fails, the output and an image highlighting the differing pixels
+//! are written to `target/golden`.
+
+#[macro_use]
+extern crate conrod;
+extern crate conrod_gfx_ll;
+extern crate gfx_hal;
+extern crate image;
+extern crate rand;
+extern crate rusttype;
+
+mod common;
+#[path = "../examples/demo/gui.rs"]
+mod gui;
+#[path = "../examples/demo/theme.rs"]
+mod theme;
+
+use common::build_demo;
+use conrod_gfx_ll::{AlphaMode, SoftwareRenderer};
+use gfx_hal::window::Extent2D;
+use image::{Rgba, RgbaImage};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const WIDTH: u32 = 600;
+const HEIGHT: u32 = 420;
+
+/// The largest difference allowed in any channel of a pixel, out of 255.
+const TOLERANCE: u8 = 2;
+
+/// Renders the demo GUI into an image `dpi_factor` times the size of the window.
+fn render_demo(dpi_factor: f64) -> RgbaImage {
+    let demo = build_demo(WIDTH as f64, HEIGHT as f64, 0);
+
+    let mut renderer = SoftwareRenderer::new(
+        Extent2D {
+            width: (WIDTH as f64 * dpi_factor) as u32,
+            height: (HEIGHT as f64 * dpi_factor) as u32,
+        },
+        AlphaMode::Straight,
+    );
+
+    renderer.set_clear_color(demo.ui.theme.background_color);
+
+    renderer.render(demo.ui.draw(), &demo.image_map, dpi_factor)
+}
+
+/// Compares `actual` with the golden image called `name`, panicking if any pixel differs by more
+/// than `TOLERANCE`.
+fn assert_matches_golden(name: &str, actual: &RgbaImage) {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let golden_path = manifest_dir
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.png", name));
+
+    if env::var_os("UPDATE_GOLDENS").is_some() {
+        fs::create_dir_all(golden_path.parent().unwrap()).unwrap();
+        actual.save(&golden_path).unwrap();
+        return;
+    }
+
+    assert!(
+        golden_path.exists(),
+        "{} has no golden image at {}; run the tests with UPDATE_GOLDENS=1 to write it",
+        name,
+        golden_path.display()
+    );
+
+    let expected = image::open(&golden_path).unwrap().to_rgba();
+
+    assert_eq!(
+        expected.dimensions(),
+        actual.dimensions(),
+        "{} has different dimensions from its golden image",
+        name
+    );
+
+    let mut diff = RgbaImage::new(actual.width(), actual.height());
+    let mut differing_pixels = 0;
+
+    for (x, y, actual_pixel) in actual.enumerate_pixels() {
+        let expected_pixel = expected.get_pixel(x, y);
+
+        let differs = actual_pixel
+            .data
+            .iter()
+            .zip(expected_pixel.data.iter())
+            .any(|(&a, &e)| (a as i16 - e as i16).abs() > TOLERANCE as i16);
+
+        let diff_pixel = if differs {
+            differing_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            // Matching pixels are dimmed so that the differences stand out.
+            let [r, g, b, _] = actual_pixel.data;
+            Rgba([r / 4, g / 4, b / 4, 255])
+        };
+
+        diff.put_pixel(x, y, diff_pixel);
+    }
+
+    if differing_pixels > 0 {
+        let output_dir = manifest_dir.join("target").join("golden");
+        fs::create_dir_all(&output_dir).unwrap();
+
+        let actual_path = output_dir.join(format!("{}.actual.png", name));
+        let diff_path = output_dir.join(format!("{}.diff.png", name));
+
+        actual.save(&actual_path).unwrap();
+        diff.save(&diff_path).unwrap();
+
+        panic!(
+            "{} pixels of {} differ from {} by more than {}; see {} and {}",
+            differing_pixels,
+            name,
+            golden_path.display(),
+            TOLERANCE,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn demo() {
+    assert_matches_golden("demo", &render_demo(1.0));
+}
+
+#[test]
+fn demo_hidpi() {
+    assert_matches_golden("demo_hidpi", &render_demo(2.0));
+}
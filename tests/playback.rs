@@ -0,0 +1,140 @@
+//! Drives the demo GUI with scripted input and checks how its state responds.
+
+#[macro_use]
+extern crate conrod;
+extern crate conrod_gfx_ll;
+extern crate image;
+extern crate rand;
+extern crate rusttype;
+
+mod common;
+#[path = "../examples/demo/gui.rs"]
+mod gui;
+#[path = "../examples/demo/theme.rs"]
+mod theme;
+
+use common::{build_demo, play, Demo};
+use conrod::event::Input;
+use conrod::input::{Button, MouseButton};
+use conrod::widget;
+use conrod::Rect;
+use conrod_gfx_ll::playback::ScriptedInput;
+use conrod_gfx_ll::{Playback, Script};
+
+const WIDTH: f64 = 600.0;
+
+/// Tall enough for the widgets under test to be visible without scrolling.
+const HEIGHT: f64 = 3000.0;
+
+/// Returns the rectangle of `id`, checking that it is within the window so that it can be
+/// clicked.
+fn visible_rect(demo: &Demo, id: widget::Id) -> Rect {
+    let rect = demo.ui.rect_of(id).unwrap();
+    let window = demo.ui.rect_of(demo.ui.window).unwrap();
+
+    assert!(
+        window.is_over(rect.top_left()) && window.is_over(rect.bottom_right()),
+        "{:?} is outside of the window {:?}",
+        rect,
+        window
+    );
+
+    rect
+}
+
+#[test]
+fn playback_handles_inputs_on_their_frames() {
+    let press = Input::Press(Button::Mouse(MouseButton::Left));
+    let release = Input::Release(Button::Mouse(MouseButton::Left));
+
+    let inputs = Script::new()
+        .press(MouseButton::Left)
+        .wait(2)
+        .release(MouseButton::Left)
+        .build();
+
+    assert_eq!(
+        inputs,
+        vec![
+            ScriptedInput {
+                frame: 0,
+                input: press,
+            },
+            ScriptedInput {
+                frame: 3,
+                input: release,
+            },
+        ]
+    );
+
+    let mut demo = build_demo(WIDTH, HEIGHT, 0);
+    let mut playback = Playback::new(inputs);
+
+    assert_eq!(playback.last_frame(), 3);
+
+    for _ in 0..3 {
+        playback.step(&mut demo.ui);
+        assert!(!playback.is_finished());
+    }
+
+    playback.step(&mut demo.ui);
+    assert!(playback.is_finished());
+    assert_eq!(playback.frame(), 4);
+}
+
+#[test]
+fn clicking_toggle_flips_ball_color() {
+    let mut demo = build_demo(WIDTH, HEIGHT, 0);
+    assert_eq!(demo.state.ball_color(), conrod::color::WHITE);
+
+    let toggle = visible_rect(&demo, demo.ids.toggle);
+
+    play(
+        &mut demo,
+        Playback::new(Script::new().click(toggle.xy()).build()),
+    );
+    assert_eq!(demo.state.ball_color(), conrod::color::BLACK);
+
+    play(
+        &mut demo,
+        Playback::new(Script::new().click(toggle.xy()).build()),
+    );
+    assert_eq!(demo.state.ball_color(), conrod::color::WHITE);
+}
+
+#[test]
+fn dragging_in_xy_pad_moves_ball() {
+    let mut demo = build_demo(WIDTH, HEIGHT, 0);
+    assert_eq!(demo.state.ball_xy(), [0.0, 0.0]);
+
+    let xy_pad = visible_rect(&demo, demo.ids.xy_pad);
+    let target = [xy_pad.x() + xy_pad.w() / 4.0, xy_pad.y() + xy_pad.h() / 4.0];
+
+    play(
+        &mut demo,
+        Playback::new(Script::new().drag(xy_pad.xy(), target, 4).build()),
+    );
+
+    let [x, y] = demo.state.ball_xy();
+    assert!(x > 0.0 && y > 0.0, "ball_xy is {:?}", [x, y]);
+}
+
+#[test]
+fn pressing_button_with_same_seed_moves_ball_to_same_place() {
+    let mut ball_positions = Vec::new();
+
+    for _ in 0..2 {
+        let mut demo = build_demo(WIDTH, HEIGHT, 42);
+        let button = visible_rect(&demo, demo.ids.button);
+
+        play(
+            &mut demo,
+            Playback::new(Script::new().click(button.xy()).click(button.xy()).build()),
+        );
+
+        ball_positions.push(demo.state.ball_xy());
+    }
+
+    assert_ne!(ball_positions[0], [0.0, 0.0]);
+    assert_eq!(ball_positions[0], ball_positions[1]);
+}
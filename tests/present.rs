@@ -0,0 +1,67 @@
+extern crate conrod_gfx_ll;
+extern crate gfx_hal;
+
+use conrod_gfx_ll::PresentPolicy;
+use gfx_hal::window::PresentMode;
+
+const ALL_MODES: [PresentMode; 4] = [
+    PresentMode::Immediate,
+    PresentMode::Mailbox,
+    PresentMode::Fifo,
+    PresentMode::Relaxed,
+];
+
+#[test]
+fn policies_choose_their_best_mode() {
+    assert_eq!(
+        PresentPolicy::VsyncOn.choose(&ALL_MODES),
+        PresentMode::Mailbox
+    );
+    assert_eq!(
+        PresentPolicy::VsyncOff.choose(&ALL_MODES),
+        PresentMode::Immediate
+    );
+    assert_eq!(
+        PresentPolicy::LowLatency.choose(&ALL_MODES),
+        PresentMode::Mailbox
+    );
+    assert_eq!(
+        PresentPolicy::PowerSaving.choose(&ALL_MODES),
+        PresentMode::Fifo
+    );
+}
+
+#[test]
+fn policies_fall_back_to_available_modes() {
+    let modes = [PresentMode::Fifo, PresentMode::Immediate];
+
+    assert_eq!(PresentPolicy::VsyncOn.choose(&modes), PresentMode::Fifo);
+    assert_eq!(
+        PresentPolicy::LowLatency.choose(&modes),
+        PresentMode::Immediate
+    );
+
+    for &policy in &PresentPolicy::ALL {
+        assert_eq!(policy.choose(&[PresentMode::Fifo]), PresentMode::Fifo);
+        assert_eq!(policy.choose(&[]), PresentMode::Fifo);
+    }
+}
+
+#[test]
+fn next_cycles_through_every_policy() {
+    let mut policy = PresentPolicy::default();
+
+    for &expected in PresentPolicy::ALL.iter().cycle().skip(1).take(4) {
+        policy = policy.next();
+        assert_eq!(policy, expected);
+    }
+}
+
+#[test]
+fn policies_are_parsed() {
+    assert_eq!("vsync".parse(), Ok(PresentPolicy::VsyncOn));
+    assert_eq!("no-vsync".parse(), Ok(PresentPolicy::VsyncOff));
+    assert_eq!("low-latency".parse(), Ok(PresentPolicy::LowLatency));
+    assert_eq!("power-saving".parse(), Ok(PresentPolicy::PowerSaving));
+    assert!("adaptive".parse::<PresentPolicy>().is_err());
+}
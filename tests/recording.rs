@@ -0,0 +1,79 @@
+extern crate conrod;
+extern crate conrod_gfx_ll;
+
+use conrod::event::Input;
+use conrod::input::{Button, Motion, MouseButton};
+use conrod_gfx_ll::recording::{read_recording, to_scripted_inputs, RecordingError, TimedInput};
+use conrod_gfx_ll::Recorder;
+use std::time::Duration;
+
+#[test]
+fn recorded_inputs_are_read_back_in_order() {
+    let inputs = vec![
+        Input::Motion(Motion::MouseCursor { x: 10.0, y: -20.5 }),
+        Input::Press(Button::Mouse(MouseButton::Left)),
+        Input::Release(Button::Mouse(MouseButton::Left)),
+        Input::Motion(Motion::Scroll { x: 0.0, y: 3.0 }),
+        Input::Text("héllo \"world\"\n".to_string()),
+        Input::Focus(false),
+        Input::Redraw,
+    ];
+
+    let mut recorder = Recorder::new(Vec::new());
+
+    for input in &inputs {
+        recorder.record(input).unwrap();
+    }
+
+    let recording = recorder.into_inner();
+
+    assert_eq!(
+        recording.iter().filter(|&&byte| byte == b'\n').count(),
+        inputs.len()
+    );
+
+    let timed_inputs = read_recording(&recording[..]).unwrap();
+
+    assert_eq!(
+        timed_inputs
+            .iter()
+            .map(|timed_input| timed_input.input.clone())
+            .collect::<Vec<_>>(),
+        inputs
+    );
+
+    assert!(timed_inputs
+        .windows(2)
+        .all(|pair| pair[0].time <= pair[1].time));
+}
+
+#[test]
+fn invalid_lines_are_reported_with_their_number() {
+    let recording = b"{\"time\":0.0,\"type\":\"redraw\"}\n\n{\"time\":1.0,\"type\":\"jump\"}\n";
+
+    match read_recording(&recording[..]) {
+        Err(RecordingError::Parse { line, .. }) => assert_eq!(line, 3),
+        result => panic!("Expected a parse error, got {:?}", result),
+    }
+}
+
+#[test]
+fn timed_inputs_are_scheduled_by_frame() {
+    let timed_inputs = vec![
+        TimedInput {
+            time: Duration::from_millis(5),
+            input: Input::Redraw,
+        },
+        TimedInput {
+            time: Duration::from_millis(40),
+            input: Input::Focus(true),
+        },
+    ];
+
+    let frames: Vec<_> = to_scripted_inputs(&timed_inputs, Duration::from_millis(16))
+        .into_iter()
+        .map(|scripted_input| scripted_input.frame)
+        .collect();
+
+    assert_eq!(frames, vec![0, 2]);
+}